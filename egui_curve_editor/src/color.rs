@@ -0,0 +1,89 @@
+// A plain color type for gradients and channel colors, so that they do not need egui.
+
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+/// An sRGB color with unmultiplied alpha.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct Color32(pub [u8; 4]);
+
+#[allow(unused)]
+impl Color32 {
+    pub const BLACK: Self = Self::from_rgb(0, 0, 0);
+    pub const WHITE: Self = Self::from_rgb(255, 255, 255);
+    pub const GRAY: Self = Self::from_rgb(160, 160, 160);
+    pub const RED: Self = Self::from_rgb(255, 0, 0);
+    pub const BLUE: Self = Self::from_rgb(0, 0, 255);
+    pub const YELLOW: Self = Self::from_rgb(255, 255, 0);
+    pub const TRANSPARENT: Self = Self::from_rgba_unmultiplied(0, 0, 0, 0);
+
+    pub const fn from_rgb(r: u8, g: u8, b: u8) -> Self {
+        Self([r, g, b, 255])
+    }
+
+    pub const fn from_rgba_unmultiplied(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self([r, g, b, a])
+    }
+
+    pub const fn r(&self) -> u8 {
+        self.0[0]
+    }
+
+    pub const fn g(&self) -> u8 {
+        self.0[1]
+    }
+
+    pub const fn b(&self) -> u8 {
+        self.0[2]
+    }
+
+    pub const fn a(&self) -> u8 {
+        self.0[3]
+    }
+
+    pub const fn to_srgba_unmultiplied(&self) -> [u8; 4] {
+        self.0
+    }
+}
+
+/// sRGB transfer function, from a gamma byte to a linear value in `0..=1`.
+pub fn linear_f32_from_gamma_u8(value: u8) -> f32 {
+    let value = value as f32 / 255.0;
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Inverse of [`linear_f32_from_gamma_u8`].
+pub fn gamma_u8_from_linear_f32(value: f32) -> u8 {
+    let value = value.clamp(0.0, 1.0);
+    let gamma = if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    };
+
+    (gamma * 255.0 + 0.5) as u8
+}
+
+/// A linear value in `0..=1` as a byte, like alpha.
+pub fn linear_u8_from_linear_f32(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0 + 0.5) as u8
+}
+
+#[cfg(feature = "egui")]
+impl From<Color32> for egui::Color32 {
+    fn from(color: Color32) -> Self {
+        let [r, g, b, a] = color.0;
+        egui::Color32::from_rgba_unmultiplied(r, g, b, a)
+    }
+}
+
+#[cfg(feature = "egui")]
+impl From<egui::Color32> for Color32 {
+    fn from(color: egui::Color32) -> Self {
+        Self(color.to_srgba_unmultiplied())
+    }
+}
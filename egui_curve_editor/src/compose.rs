@@ -0,0 +1,451 @@
+// Arithmetic on curves, either kept lazy through `Sample` or baked back into a `Curve`.
+//
+// A segment is a cubic polynomial in x, so sums and blends of two curves are exact once both are
+// split at the union of their keys. Other operations are resampled.
+
+use crate::curve::bezier_derivative;
+use crate::math::pos2;
+use crate::prelude::*;
+use crate::{Curve, Point, Sampleable, TangentMode};
+
+/// Arithmetic on anything [`Sampleable`] as `f32`, and baking the result into a [`Curve`].
+pub trait Sample: Sampleable<f32> {
+    /// Bakes `keys` evenly spaced samples into a curve, with tangents following the slope.
+    ///
+    /// Samples are clamped to `0..=1` before their slopes are taken, so that the curve stays flat
+    /// where the samples leave that range.
+    fn to_curve(&self, keys: usize) -> Curve
+    where
+        Self: Sized,
+    {
+        const H: f32 = 0.001;
+
+        let sample = |x: f32| self.sample(x).clamp(0.0, 1.0);
+        let keys = keys.max(2);
+        Curve::from_points((0..keys).map(|i| {
+            let x = i as f32 / (keys - 1) as f32;
+            let (before, after) = ((x - H).max(0.0), (x + H).min(1.0));
+            let slope = (sample(after) - sample(before)) / (after - before);
+
+            Point::new(
+                pos2(x, sample(x)),
+                slope,
+                slope,
+                TangentMode::Free,
+                TangentMode::Free,
+            )
+        }))
+    }
+
+    fn add<B: Sampleable<f32>>(self, other: B) -> Combine<Self, B>
+    where
+        Self: Sized,
+    {
+        Combine::new(self, other, Operation::Add)
+    }
+
+    fn subtract<B: Sampleable<f32>>(self, other: B) -> Combine<Self, B>
+    where
+        Self: Sized,
+    {
+        Combine::new(self, other, Operation::Subtract)
+    }
+
+    fn multiply<B: Sampleable<f32>>(self, other: B) -> Combine<Self, B>
+    where
+        Self: Sized,
+    {
+        Combine::new(self, other, Operation::Multiply)
+    }
+
+    fn min<B: Sampleable<f32>>(self, other: B) -> Combine<Self, B>
+    where
+        Self: Sized,
+    {
+        Combine::new(self, other, Operation::Min)
+    }
+
+    fn max<B: Sampleable<f32>>(self, other: B) -> Combine<Self, B>
+    where
+        Self: Sized,
+    {
+        Combine::new(self, other, Operation::Max)
+    }
+
+    /// `0` gives `self`, `1` gives `other`.
+    fn blend<B: Sampleable<f32>>(self, other: B, factor: f32) -> Combine<Self, B>
+    where
+        Self: Sized,
+    {
+        Combine::new(self, other, Operation::Blend(factor))
+    }
+
+    /// Samples `self` at the value of `inner`, `self(inner(x))`.
+    fn compose<G: Sampleable<f32>>(self, inner: G) -> Compose<Self, G>
+    where
+        Self: Sized,
+    {
+        Compose { outer: self, inner }
+    }
+}
+
+impl<S: Sampleable<f32> + ?Sized> Sample for S {}
+
+/// How [`Combine`] merges two values.
+#[derive(PartialEq, Clone, Copy, Debug, serde::Deserialize, serde::Serialize)]
+pub enum Operation {
+    Add,
+    Subtract,
+    Multiply,
+    Min,
+    Max,
+    Blend(f32),
+}
+
+impl Operation {
+    pub fn apply(self, a: f32, b: f32) -> f32 {
+        match self {
+            Operation::Add => a + b,
+            Operation::Subtract => a - b,
+            Operation::Multiply => a * b,
+            Operation::Min => a.min(b),
+            Operation::Max => a.max(b),
+            Operation::Blend(factor) => a + (b - a) * factor,
+        }
+    }
+
+    /// Slope of the result from the values and slopes of both sides, if the result stays cubic.
+    fn apply_slope(self, a: f32, b: f32) -> Option<f32> {
+        match self {
+            Operation::Add | Operation::Subtract | Operation::Blend(_) => Some(self.apply(a, b)),
+            Operation::Multiply | Operation::Min | Operation::Max => None,
+        }
+    }
+}
+
+/// Two samples merged by an [`Operation`], evaluated lazily.
+#[derive(Clone, Copy)]
+pub struct Combine<A, B> {
+    a: A,
+    b: B,
+    operation: Operation,
+}
+
+impl<A: Sampleable<f32>, B: Sampleable<f32>> Combine<A, B> {
+    pub fn new(a: A, b: B, operation: Operation) -> Self {
+        Self { a, b, operation }
+    }
+}
+
+impl<A: Sampleable<f32>, B: Sampleable<f32>> Sampleable<f32> for Combine<A, B> {
+    fn sample(&self, x: f32) -> f32 {
+        self.operation.apply(self.a.sample(x), self.b.sample(x))
+    }
+}
+
+/// `outer(inner(x))`, evaluated lazily.
+#[derive(Clone, Copy)]
+pub struct Compose<F, G> {
+    outer: F,
+    inner: G,
+}
+
+impl<F: Sampleable<f32>, G: Sampleable<f32>> Sampleable<f32> for Compose<F, G> {
+    fn sample(&self, x: f32) -> f32 {
+        self.outer.sample(self.inner.sample(x))
+    }
+}
+
+#[allow(unused)]
+impl Curve {
+    /// Merges two curves into one without resampling, keeping a key wherever either has one.
+    ///
+    /// Only [`Operation::Add`], [`Operation::Subtract`] and [`Operation::Blend`] keep segments
+    /// cubic, other operations give `None` and need [`Sample::to_curve`]. So does a result with a
+    /// key outside `0..=1`, which a curve cannot hold. Between keys, the result is clamped to `0..=1`
+    /// when sampled like any curve.
+    pub fn combine(&self, other: &Curve, operation: Operation) -> Option<Curve> {
+        const EPSILON: f32 = 0.00001;
+
+        operation.apply_slope(0.0, 0.0)?;
+        if self.points().is_empty() || other.points().is_empty() {
+            return None;
+        }
+
+        let mut xs: Vec<f32> = self
+            .points()
+            .iter()
+            .chain(other.points())
+            .map(|point| point.pos().x)
+            .collect();
+        xs.sort_by(f32::total_cmp);
+        xs.dedup_by(|a, b| (*a - *b).abs() < EPSILON);
+
+        let mut points = vec![];
+        for x in xs {
+            let (a_left, a_right) = self.one_sided(x);
+            let (b_left, b_right) = other.one_sided(x);
+
+            let value = |a: (f32, f32), b: (f32, f32)| operation.apply(a.0, b.0);
+            let slope = |a: (f32, f32), b: (f32, f32)| operation.apply_slope(a.1, b.1).unwrap();
+
+            let (left, right) = (value(a_left, b_left), value(a_right, b_right));
+            let inside = |value: f32| (-EPSILON..=1.0 + EPSILON).contains(&value);
+            if !inside(left) || !inside(right) {
+                return None;
+            }
+            if (left - right).abs() < EPSILON {
+                points.push(Point::new(
+                    pos2(x, left),
+                    slope(a_left, b_left),
+                    slope(a_right, b_right),
+                    TangentMode::Free,
+                    TangentMode::Free,
+                ));
+            } else {
+                // Either side jumps here, so does the result
+                points.push(Point::new(
+                    pos2(x, left),
+                    slope(a_left, b_left),
+                    0.0,
+                    TangentMode::Free,
+                    TangentMode::Free,
+                ));
+                points.push(Point::new(
+                    pos2(x, right),
+                    0.0,
+                    slope(a_right, b_right),
+                    TangentMode::Free,
+                    TangentMode::Free,
+                ));
+            }
+        }
+
+        Some(Curve::from_points(points))
+    }
+
+    /// Value and slope when reaching `x` from the left and when leaving it to the right.
+    ///
+    /// The curve is flat before its first point and after its last.
+    fn one_sided(&self, x: f32) -> ((f32, f32), (f32, f32)) {
+        const EPSILON: f32 = 0.00001;
+
+        let points = self.points();
+        let last = points.len() - 1;
+
+        let keys: Vec<usize> = (0..points.len())
+            .filter(|&index| (points[index].pos().x - x).abs() < EPSILON)
+            .collect();
+        if let (Some(&first_key), Some(&last_key)) = (keys.first(), keys.last()) {
+            let left = if first_key == 0 {
+                0.0
+            } else {
+                points[first_key].left_tan()
+            };
+            let right = if last_key == last {
+                0.0
+            } else {
+                points[last_key].right_tan()
+            };
+
+            return (
+                (points[first_key].pos().y, left),
+                (points[last_key].pos().y, right),
+            );
+        }
+
+        if x < points[0].pos().x || x > points[last].pos().x {
+            let value = Curve::sample(self, x);
+            return ((value, 0.0), (value, 0.0));
+        }
+
+        let index = self.get_index(x);
+        let [a, c1, c2, b] = self.segment_bezier(index).unwrap();
+        let t = (x - a.x) / (b.x - a.x);
+        let slope = bezier_derivative(a.y, c1.y, c2.y, b.y, t) / (b.x - a.x);
+        let value = Curve::sample(self, x);
+
+        ((value, slope), (value, slope))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sampleable::{from_fn, Constant};
+
+    fn line(from: f32, to: f32) -> Curve {
+        Curve::from_points([
+            Point::from_pos(pos2(0.0, from)),
+            Point::from_pos(pos2(1.0, to)),
+        ])
+    }
+
+    fn wave() -> Curve {
+        Curve::from_points([
+            Point::new(
+                pos2(0.0, 0.2),
+                0.0,
+                1.0,
+                TangentMode::Free,
+                TangentMode::Free,
+            ),
+            Point::new(
+                pos2(0.3, 0.6),
+                0.0,
+                0.0,
+                TangentMode::Free,
+                TangentMode::Free,
+            ),
+            Point::new(
+                pos2(0.8, 0.1),
+                -0.5,
+                0.5,
+                TangentMode::Free,
+                TangentMode::Free,
+            ),
+        ])
+    }
+
+    fn assert_matches(curve: &Curve, expected: impl Sampleable<f32>) {
+        for i in 0..=50 {
+            let x = i as f32 / 50.0;
+            let (value, expected) = (curve.sample(x), expected.sample(x));
+            assert!(
+                (value - expected).abs() < 0.0001,
+                "sample({x}) = {value}, expected {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn applies_operations() {
+        assert_eq!(Operation::Add.apply(0.25, 0.5), 0.75);
+        assert_eq!(Operation::Subtract.apply(0.25, 0.5), -0.25);
+        assert_eq!(Operation::Multiply.apply(0.25, 0.5), 0.125);
+        assert_eq!(Operation::Min.apply(0.25, 0.5), 0.25);
+        assert_eq!(Operation::Max.apply(0.25, 0.5), 0.5);
+        assert_eq!(Operation::Blend(0.25).apply(0.0, 1.0), 0.25);
+    }
+
+    #[test]
+    fn samples_lazily() {
+        let a = line(0.0, 1.0);
+        let b = Constant(0.5f32);
+
+        assert_eq!((&a).add(b).sample(0.5), 1.0);
+        assert_eq!((&a).multiply(b).sample(0.5), 0.25);
+        assert_eq!((&a).min(b).sample(0.8), 0.5);
+        assert_eq!((&a).max(b).sample(0.8), 0.8);
+        assert_eq!((&a).blend(b, 0.5).sample(1.0), 0.75);
+
+        // Curves are flat outside their keys, so the composition clamps
+        let composed = (&a).compose(from_fn(|x: f32| x * 2.0));
+        assert_eq!(composed.sample(0.25), 0.5);
+        assert_eq!(composed.sample(1.0), 1.0);
+    }
+
+    #[test]
+    fn combines_exactly() {
+        let (a, b) = (wave(), line(0.1, 0.3));
+
+        let sum = a.combine(&b, Operation::Add).unwrap();
+        assert_matches(
+            &sum,
+            from_fn(|x| (a.sample(x) + b.sample(x)).clamp(0.0, 1.0)),
+        );
+
+        let blend = a.combine(&b, Operation::Blend(0.3)).unwrap();
+        assert_matches(&blend, (&a).blend(&b, 0.3));
+
+        // Keys of both sides are kept
+        assert_eq!(sum.points().len(), 4);
+    }
+
+    #[test]
+    fn rejects_results_outside_unit_range() {
+        let line = line(0.0, 1.0);
+
+        assert!(line.combine(&line, Operation::Add).is_none());
+        assert!(line
+            .combine(
+                &Curve::from_points([Point::from_pos(pos2(0.5, 0.5))]),
+                Operation::Subtract
+            )
+            .is_none());
+
+        // Baking clamps instead
+        let baked = (&line).subtract(Constant(0.5f32)).to_curve(65);
+        assert!(baked.sample(0.1).abs() < 0.0001);
+        assert!(baked.sample(0.5).abs() < 0.0001);
+        assert!((baked.sample(0.75) - 0.25).abs() < 0.001);
+    }
+
+    #[test]
+    fn clamps_overshoot_between_keys() {
+        // Keys stay inside, the sum overshoots between them
+        let a = Curve::from_points([
+            Point::new(
+                pos2(0.0, 0.5),
+                0.0,
+                4.0,
+                TangentMode::Free,
+                TangentMode::Free,
+            ),
+            Point::new(
+                pos2(1.0, 0.5),
+                4.0,
+                0.0,
+                TangentMode::Free,
+                TangentMode::Free,
+            ),
+        ]);
+        let b = line(0.4, 0.4);
+        let sum = a.combine(&b, Operation::Add).unwrap();
+
+        assert_matches(
+            &sum,
+            from_fn(|x| (a.sample(x) + b.sample(x)).clamp(0.0, 1.0)),
+        );
+        assert_eq!(sum.sample(0.3), 1.0);
+    }
+
+    #[test]
+    fn combines_jumps() {
+        let step = Curve::from_points([
+            Point::from_pos(pos2(0.0, 0.0)),
+            Point::from_pos(pos2(0.5, 0.0)),
+            Point::from_pos(pos2(0.5, 0.5)),
+            Point::from_pos(pos2(1.0, 0.5)),
+        ]);
+        let sum = step.combine(&line(0.2, 0.2), Operation::Add).unwrap();
+
+        assert!((sum.sample(0.4) - 0.2).abs() < 0.0001);
+        assert!((sum.sample(0.6) - 0.7).abs() < 0.0001);
+    }
+
+    #[test]
+    fn combines_single_keys_and_empty_curves() {
+        let single = Curve::from_points([Point::from_pos(pos2(0.5, 0.25))]);
+        let sum = single.combine(&line(0.0, 0.5), Operation::Add).unwrap();
+        assert_matches(&sum, from_fn(|x| 0.25 + x * 0.5));
+
+        assert!(Curve::default().combine(&single, Operation::Add).is_none());
+        assert!(single.combine(&single, Operation::Multiply).is_none());
+    }
+
+    #[test]
+    fn bakes_samples() {
+        let a = wave();
+        let baked = (&a).multiply(Constant(0.5f32)).to_curve(64);
+
+        assert_eq!(baked.points().len(), 64);
+        for i in 0..=20 {
+            let x = i as f32 / 20.0;
+            assert!((baked.sample(x) - a.sample(x) * 0.5).abs() < 0.01);
+        }
+
+        // At least both ends are kept
+        assert_eq!(Constant(0.5f32).to_curve(0).points().len(), 2);
+    }
+}
@@ -0,0 +1,457 @@
+// CSS easing functions, as used by `transition-timing-function` and `animation-timing-function`.
+
+use crate::curve::bezier_to_points;
+use crate::math::{pos2, Pos2};
+use crate::prelude::*;
+use crate::{Curve, ParseError, Point, TangentMode};
+
+/// How steps of a `steps()` easing are placed, see the CSS `<step-position>`.
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub enum StepPosition {
+    JumpStart,
+    #[default]
+    JumpEnd,
+    JumpNone,
+    JumpBoth,
+}
+
+#[allow(unused)]
+impl Curve {
+    /// Parses a CSS `<easing-function>`: a keyword, `cubic-bezier()`, `steps()` or `linear()`.
+    ///
+    /// Values are clamped to `0..=1` like every curve: a `cubic-bezier()` with y values outside
+    /// `0..1`, as in back easings, is flattened where it overshoots, and so are `linear()` stops.
+    pub fn from_css_easing(text: &str) -> Result<Self, ParseError> {
+        let text = text.trim().to_ascii_lowercase();
+
+        match text.as_str() {
+            "linear" => return Ok(css_linear(&[(0.0, 0.0), (1.0, 1.0)])),
+            "ease" => return Ok(css_cubic_bezier(0.25, 0.1, 0.25, 1.0)),
+            "ease-in" => return Ok(css_cubic_bezier(0.42, 0.0, 1.0, 1.0)),
+            "ease-out" => return Ok(css_cubic_bezier(0.0, 0.0, 0.58, 1.0)),
+            "ease-in-out" => return Ok(css_cubic_bezier(0.42, 0.0, 0.58, 1.0)),
+            "step-start" => return Ok(css_steps(1, StepPosition::JumpStart)),
+            "step-end" => return Ok(css_steps(1, StepPosition::JumpEnd)),
+            _ => {}
+        }
+
+        let (name, arguments) = text
+            .strip_suffix(')')
+            .and_then(|text| text.split_once('('))
+            .ok_or_else(|| ParseError::new(format!("unknown easing function `{text}`")))?;
+        let arguments: Vec<&str> = arguments.split(',').map(str::trim).collect();
+
+        match name.trim() {
+            "cubic-bezier" => {
+                let [x1, y1, x2, y2] = arguments[..] else {
+                    return Err(ParseError::new("cubic-bezier() takes 4 numbers"));
+                };
+                let (x1, y1, x2, y2) = (
+                    parse_number(x1)?,
+                    parse_number(y1)?,
+                    parse_number(x2)?,
+                    parse_number(y2)?,
+                );
+                if !(0.0..=1.0).contains(&x1) || !(0.0..=1.0).contains(&x2) {
+                    return Err(ParseError::new("cubic-bezier() x values must be in 0..1"));
+                }
+
+                Ok(css_cubic_bezier(x1, y1, x2, y2))
+            }
+            "steps" => {
+                let (count, position) = match arguments[..] {
+                    [count] => (count, StepPosition::default()),
+                    [count, position] => (count, parse_step_position(position)?),
+                    _ => return Err(ParseError::new("steps() takes a count and a position")),
+                };
+                let count: usize = count
+                    .parse()
+                    .map_err(|_| ParseError::new(format!("invalid step count `{count}`")))?;
+
+                let minimum = if position == StepPosition::JumpNone {
+                    2
+                } else {
+                    1
+                };
+                if count < minimum {
+                    return Err(ParseError::new(format!(
+                        "steps() needs at least {minimum} steps"
+                    )));
+                }
+
+                Ok(css_steps(count, position))
+            }
+            "linear" => Ok(css_linear(&parse_linear_stops(&arguments)?)),
+            name => Err(ParseError::new(format!("unknown easing function `{name}`"))),
+        }
+    }
+
+    /// Writes the curve as `cubic-bezier()` if it is a single segment from `(0, 0)` to `(1, 1)`.
+    pub fn to_css_cubic_bezier(&self) -> Option<String> {
+        let [a, b] = self.points() else {
+            return None;
+        };
+        if a.pos() != pos2(0.0, 0.0) || b.pos() != pos2(1.0, 1.0) {
+            return None;
+        }
+
+        // Control points at equal distances
+        Some(format!(
+            "cubic-bezier({}, {}, {}, {})",
+            format_number(1.0 / 3.0),
+            format_number(a.right_tan() / 3.0),
+            format_number(2.0 / 3.0),
+            format_number(1.0 - b.left_tan() / 3.0),
+        ))
+    }
+
+    /// Writes the curve as `linear()`, with at most `max_points` stops.
+    ///
+    /// Stops are added where the approximation is the furthest off, until it is within `tolerance`.
+    pub fn to_css_linear(&self, max_points: usize, tolerance: f32) -> String {
+        const RESOLUTION: usize = 1000;
+
+        let samples: Vec<Pos2> = (0..=RESOLUTION)
+            .map(|i| {
+                let x = i as f32 / RESOLUTION as f32;
+                pos2(x, self.sample(x))
+            })
+            .collect();
+
+        let samples = &samples;
+
+        // Greedily insert the worst sample between the stops kept so far
+        let mut kept = vec![0, RESOLUTION];
+        while kept.len() < max_points.max(2) {
+            let worst = kept
+                .windows(2)
+                .flat_map(|stops| {
+                    let (a, b) = (samples[stops[0]], samples[stops[1]]);
+                    (stops[0] + 1..stops[1]).map(move |i| {
+                        let t = (samples[i].x - a.x) / (b.x - a.x);
+                        (i, (a.y + (b.y - a.y) * t - samples[i].y).abs())
+                    })
+                })
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+
+            match worst {
+                Some((i, error)) if error > tolerance => {
+                    let position = kept.partition_point(|&kept| kept < i);
+                    kept.insert(position, i);
+                }
+                _ => break,
+            }
+        }
+
+        let stops: Vec<String> = kept
+            .iter()
+            .enumerate()
+            .map(|(n, &i)| {
+                let pos = samples[i];
+                if n == 0 || n == kept.len() - 1 {
+                    format_number(pos.y)
+                } else {
+                    format!("{} {}%", format_number(pos.y), format_number(pos.x * 100.0))
+                }
+            })
+            .collect();
+
+        format!("linear({})", stops.join(", "))
+    }
+}
+
+fn css_cubic_bezier(x1: f32, y1: f32, x2: f32, y2: f32) -> Curve {
+    const TOLERANCE: f32 = 0.001;
+
+    Curve::from_points(bezier_to_points(
+        [pos2(0.0, 0.0), pos2(x1, y1), pos2(x2, y2), pos2(1.0, 1.0)],
+        TOLERANCE,
+    ))
+}
+
+fn css_steps(count: usize, position: StepPosition) -> Curve {
+    let value = |i: usize| match position {
+        StepPosition::JumpStart => (i + 1) as f32 / count as f32,
+        StepPosition::JumpEnd => i as f32 / count as f32,
+        StepPosition::JumpNone => i as f32 / (count - 1) as f32,
+        StepPosition::JumpBoth => (i + 1) as f32 / (count + 1) as f32,
+    };
+
+    let flat =
+        |x: f32, y: f32| Point::new(pos2(x, y), 0.0, 0.0, TangentMode::Free, TangentMode::Free);
+
+    // Each step is a flat segment, consecutive steps meet in a jump
+    let mut points = vec![];
+    for i in 0..count {
+        points.push(flat(i as f32 / count as f32, value(i)));
+        points.push(flat((i + 1) as f32 / count as f32, value(i)));
+    }
+    if value(count - 1) != 1.0 {
+        points.push(flat(1.0, 1.0));
+    }
+
+    Curve::from_points(points)
+}
+
+fn css_linear(stops: &[(f32, f32)]) -> Curve {
+    Curve::from_points(stops.iter().map(|&(x, y)| Point::from_pos(pos2(x, y))))
+}
+
+/// Resolves the stops of `linear()` into `(input, output)` pairs.
+fn parse_linear_stops(arguments: &[&str]) -> Result<Vec<(f32, f32)>, ParseError> {
+    let mut stops: Vec<(Option<f32>, f32)> = vec![];
+    for argument in arguments {
+        let mut value = None;
+        let mut inputs = vec![];
+        for token in argument.split_whitespace() {
+            if let Some(percentage) = token.strip_suffix('%') {
+                inputs.push(parse_number(percentage)? / 100.0);
+            } else if value.is_none() {
+                value = Some(parse_number(token)?);
+            } else {
+                return Err(ParseError::new(format!(
+                    "invalid linear() stop `{argument}`"
+                )));
+            }
+        }
+
+        let value = value
+            .ok_or_else(|| ParseError::new(format!("linear() stop `{argument}` has no value")))?;
+        match inputs[..] {
+            [] => stops.push((None, value)),
+            [input] => stops.push((Some(input), value)),
+            [start, end] => {
+                stops.push((Some(start), value));
+                stops.push((Some(end), value));
+            }
+            _ => {
+                return Err(ParseError::new(format!(
+                    "invalid linear() stop `{argument}`"
+                )))
+            }
+        }
+    }
+
+    if stops.len() < 2 {
+        return Err(ParseError::new("linear() needs at least 2 stops"));
+    }
+
+    // The ends default to 0% and 100%, inputs never go back
+    let last = stops.len() - 1;
+    stops[0].0.get_or_insert(0.0);
+    stops[last].0.get_or_insert(1.0);
+    let mut largest = f32::NEG_INFINITY;
+    for stop in stops.iter_mut() {
+        if let Some(input) = stop.0.as_mut() {
+            *input = input.max(largest);
+            largest = *input;
+        }
+    }
+
+    // Missing inputs are spread evenly between their known neighbors
+    let mut resolved: Vec<(f32, f32)> = vec![];
+    let mut pending = vec![];
+    for &(input, value) in &stops {
+        let Some(input) = input else {
+            pending.push(value);
+            continue;
+        };
+
+        if let Some(&(start, _)) = resolved.last() {
+            let gap = pending.len() + 1;
+            for (j, value) in pending.drain(..).enumerate() {
+                let t = (j + 1) as f32 / gap as f32;
+                resolved.push((start + (input - start) * t, value));
+            }
+        }
+        resolved.push((input, value));
+    }
+
+    Ok(resolved)
+}
+
+fn parse_step_position(text: &str) -> Result<StepPosition, ParseError> {
+    match text {
+        "jump-start" | "start" => Ok(StepPosition::JumpStart),
+        "jump-end" | "end" => Ok(StepPosition::JumpEnd),
+        "jump-none" => Ok(StepPosition::JumpNone),
+        "jump-both" => Ok(StepPosition::JumpBoth),
+        _ => Err(ParseError::new(format!("invalid step position `{text}`"))),
+    }
+}
+
+fn parse_number(text: &str) -> Result<f32, ParseError> {
+    text.trim()
+        .parse::<f32>()
+        .ok()
+        .filter(|value| value.is_finite())
+        .ok_or_else(|| ParseError::new(format!("invalid number `{text}`")))
+}
+
+fn format_number(value: f32) -> String {
+    let text = format!("{:.4}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+
+    if text == "-0" {
+        "0".to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_keywords() {
+        for keyword in [
+            "linear",
+            "ease",
+            "ease-in",
+            "ease-out",
+            "ease-in-out",
+            " EASE ",
+        ] {
+            let curve = Curve::from_css_easing(keyword).unwrap();
+            assert_eq!(curve.sample(0.0), 0.0, "{keyword}");
+            assert!((curve.sample(1.0) - 1.0).abs() < 0.0001, "{keyword}");
+        }
+
+        let linear = Curve::from_css_easing("linear").unwrap();
+        assert!((linear.sample(0.3) - 0.3).abs() < 0.0001);
+
+        let ease_in = Curve::from_css_easing("ease-in").unwrap();
+        assert!(ease_in.sample(0.5) < 0.5);
+    }
+
+    #[test]
+    fn parses_cubic_bezier() {
+        let curve = Curve::from_css_easing("cubic-bezier(0.42, 0, 0.58, 1)").unwrap();
+
+        // Symmetric around the middle
+        assert!((curve.sample(0.5) - 0.5).abs() < 0.002);
+        assert!((curve.sample(0.2) + curve.sample(0.8) - 1.0).abs() < 0.002);
+        assert!(curve.is_monotonic());
+    }
+
+    #[test]
+    fn clamps_overshooting_cubic_bezier() {
+        let curve = Curve::from_css_easing("cubic-bezier(0.68, -0.55, 0.27, 1.55)").unwrap();
+
+        // The analysis agrees with the clamped samples
+        assert_eq!(curve.sample(0.1), 0.0);
+        assert_eq!(curve.sample(0.9), 1.0);
+        assert_eq!(
+            curve.value_range(),
+            Some(crate::math::Rangef::new(0.0, 1.0))
+        );
+    }
+
+    #[test]
+    fn parses_steps() {
+        let end = Curve::from_css_easing("steps(4)").unwrap();
+        assert_eq!(end.sample(0.1), 0.0);
+        assert_eq!(end.sample(0.3), 0.25);
+        assert_eq!(end.sample(1.0), 1.0);
+
+        let start = Curve::from_css_easing("steps(2, jump-start)").unwrap();
+        assert_eq!(start.sample(0.1), 0.5);
+
+        let none = Curve::from_css_easing("steps(3, jump-none)").unwrap();
+        assert_eq!(none.sample(0.1), 0.0);
+        assert!((none.sample(0.5) - 0.5).abs() < 0.0001);
+
+        let both = Curve::from_css_easing("steps(1, jump-both)").unwrap();
+        assert!((both.sample(0.5) - 0.5).abs() < 0.0001);
+
+        assert_eq!(Curve::from_css_easing("step-end").unwrap().sample(0.5), 0.0);
+    }
+
+    #[test]
+    fn parses_linear_stops() {
+        let curve = Curve::from_css_easing("linear(0, 0.25 75%, 1)").unwrap();
+        assert!((curve.sample(0.75) - 0.25).abs() < 0.0001);
+        assert!((curve.sample(0.375) - 0.125).abs() < 0.0001);
+
+        // Missing inputs are spread out, inputs never go back
+        let curve = Curve::from_css_easing("linear(0, 0.5, 0.5, 1)").unwrap();
+        assert_eq!(curve.get_position(1).unwrap().x, 1.0 / 3.0);
+        let curve = Curve::from_css_easing("linear(0, 0.5 60%, 0.8 40%, 1)").unwrap();
+        assert_eq!(curve.get_position(2).unwrap().x, 0.6);
+
+        // A stop with two inputs holds its value
+        let curve = Curve::from_css_easing("linear(0, 0.5 25% 75%, 1)").unwrap();
+        assert!((curve.sample(0.5) - 0.5).abs() < 0.0001);
+    }
+
+    #[test]
+    fn rejects_invalid_easings() {
+        for text in [
+            "",
+            "bounce",
+            "cubic-bezier(0, 0, 1)",
+            "cubic-bezier(1.5, 0, 0.5, 1)",
+            "cubic-bezier(0, nan, 0.5, 1)",
+            "steps(0)",
+            "steps(1, jump-none)",
+            "steps(2, middle)",
+            "linear(0)",
+            "linear(0 10% 20% 30%, 1)",
+            "linear(0, 50%)",
+        ] {
+            assert!(Curve::from_css_easing(text).is_err(), "{text}");
+        }
+    }
+
+    #[test]
+    fn writes_cubic_bezier() {
+        let curve = Curve::from_points([
+            Point::new(
+                pos2(0.0, 0.0),
+                0.0,
+                0.0,
+                TangentMode::Free,
+                TangentMode::Free,
+            ),
+            Point::new(
+                pos2(1.0, 1.0),
+                0.0,
+                0.0,
+                TangentMode::Free,
+                TangentMode::Free,
+            ),
+        ]);
+        let text = curve.to_css_cubic_bezier().unwrap();
+        assert_eq!(text, "cubic-bezier(0.3333, 0, 0.6667, 1)");
+
+        let parsed = Curve::from_css_easing(&text).unwrap();
+        for i in 0..=10 {
+            let x = i as f32 / 10.0;
+            assert!((parsed.sample(x) - curve.sample(x)).abs() < 0.002);
+        }
+
+        assert_eq!(Curve::default().to_css_cubic_bezier(), None);
+        assert_eq!(
+            Curve::from_css_easing("steps(2)")
+                .unwrap()
+                .to_css_cubic_bezier(),
+            None
+        );
+    }
+
+    #[test]
+    fn writes_linear() {
+        let curve = Curve::from_css_easing("linear(0, 1 50%, 0)").unwrap();
+        assert_eq!(curve.to_css_linear(8, 0.001), "linear(0, 1 50%, 0)");
+
+        let eased = Curve::from_css_easing("ease").unwrap();
+        let text = eased.to_css_linear(16, 0.01);
+        let parsed = Curve::from_css_easing(&text).unwrap();
+        for i in 0..=20 {
+            let x = i as f32 / 20.0;
+            assert!((parsed.sample(x) - eased.sample(x)).abs() < 0.02);
+        }
+        assert_eq!(eased.to_css_linear(0, 0.0).matches(',').count(), 1);
+    }
+}
@@ -0,0 +1,486 @@
+// Comma separated tables, as kept in spreadsheets.
+
+use crate::math::{pos2, Rangef};
+use crate::prelude::*;
+use crate::{Curve, ParseError, Point, TangentMode};
+
+/// Where a column is found in a table, by header name or by 0-based position.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CsvColumn {
+    Name(String),
+    Index(usize),
+}
+
+impl From<&str> for CsvColumn {
+    fn from(name: &str) -> Self {
+        CsvColumn::Name(name.to_string())
+    }
+}
+
+impl From<usize> for CsvColumn {
+    fn from(index: usize) -> Self {
+        CsvColumn::Index(index)
+    }
+}
+
+/// A [`Curve`] together with the ranges its table values are mapped from.
+#[derive(Clone)]
+pub struct CsvCurve {
+    pub curve: Curve,
+    pub x_range: Rangef,
+    pub y_range: Rangef,
+}
+
+impl Default for CsvCurve {
+    fn default() -> Self {
+        Self {
+            curve: Curve::default(),
+            x_range: Rangef::new(0.0, 1.0),
+            y_range: Rangef::new(0.0, 1.0),
+        }
+    }
+}
+
+#[allow(unused)]
+impl CsvCurve {
+    pub fn new(curve: Curve) -> Self {
+        Self {
+            curve,
+            ..Default::default()
+        }
+    }
+
+    /// Writes `resolution + 1` evenly spaced `x,y` samples.
+    pub fn to_csv_samples(&self, resolution: usize) -> String {
+        let resolution = resolution.max(1);
+
+        let mut text = String::from("x,y\n");
+        for i in 0..=resolution {
+            let x = i as f32 / resolution as f32;
+            text += &format!(
+                "{},{}\n",
+                self.x_range.min + x * self.x_range.span(),
+                self.y_range.min + self.curve.sample(x) * self.y_range.span()
+            );
+        }
+
+        text
+    }
+
+    /// Writes every point with its tangents and tangent modes.
+    pub fn to_csv_keys(&self) -> String {
+        let scale = self.y_range.span() / self.x_range.span();
+
+        let mut text = String::from("x,y,left_tan,right_tan,left_mode,right_mode\n");
+        for point in self.curve.points() {
+            text += &format!(
+                "{},{},{},{},{},{}\n",
+                self.x_range.min + point.pos().x * self.x_range.span(),
+                self.y_range.min + point.pos().y * self.y_range.span(),
+                point.left_tan() * scale,
+                point.right_tan() * scale,
+                mode_to_str(point.left_mode()),
+                mode_to_str(point.right_mode()),
+            );
+        }
+
+        text
+    }
+}
+
+/// Settings to read points from a table, see [`CsvImport::parse`].
+///
+/// Points without tangents get linear tangents, points with tangents but no modes get free ones.
+#[derive(Clone)]
+pub struct CsvImport {
+    x: CsvColumn,
+    y: CsvColumn,
+    left_tan: Option<CsvColumn>,
+    right_tan: Option<CsvColumn>,
+    left_mode: Option<CsvColumn>,
+    right_mode: Option<CsvColumn>,
+    delimiter: char,
+    header: bool,
+    fit_ranges: bool,
+}
+
+impl Default for CsvImport {
+    /// Reads the columns written by [`CsvCurve::to_csv_keys`].
+    fn default() -> Self {
+        Self {
+            x: "x".into(),
+            y: "y".into(),
+            left_tan: Some("left_tan".into()),
+            right_tan: Some("right_tan".into()),
+            left_mode: Some("left_mode".into()),
+            right_mode: Some("right_mode".into()),
+            delimiter: ',',
+            header: true,
+            fit_ranges: false,
+        }
+    }
+}
+
+#[allow(unused)]
+impl CsvImport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_x(self, x: impl Into<CsvColumn>) -> Self {
+        Self {
+            x: x.into(),
+            ..self
+        }
+    }
+
+    pub fn with_y(self, y: impl Into<CsvColumn>) -> Self {
+        Self {
+            y: y.into(),
+            ..self
+        }
+    }
+
+    pub fn with_left_tan(self, left_tan: Option<CsvColumn>) -> Self {
+        Self { left_tan, ..self }
+    }
+
+    pub fn with_right_tan(self, right_tan: Option<CsvColumn>) -> Self {
+        Self { right_tan, ..self }
+    }
+
+    pub fn with_left_mode(self, left_mode: Option<CsvColumn>) -> Self {
+        Self { left_mode, ..self }
+    }
+
+    pub fn with_right_mode(self, right_mode: Option<CsvColumn>) -> Self {
+        Self { right_mode, ..self }
+    }
+
+    pub fn with_delimiter(self, delimiter: char) -> Self {
+        Self { delimiter, ..self }
+    }
+
+    /// Whether the first row names the columns, named columns need it.
+    pub fn with_header(self, header: bool) -> Self {
+        Self { header, ..self }
+    }
+
+    /// Maps the range of the table values to `0..=1` instead of clamping them.
+    pub fn with_fit_ranges(self, fit_ranges: bool) -> Self {
+        Self { fit_ranges, ..self }
+    }
+
+    pub fn parse(&self, text: &str) -> Result<CsvCurve, ParseError> {
+        let mut rows = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+        let header: Vec<String> = if self.header {
+            let (_, line) = rows
+                .next()
+                .ok_or_else(|| ParseError::new("the table has no header"))?;
+            split_row(line, self.delimiter)
+                .into_iter()
+                .map(str::to_string)
+                .collect()
+        } else {
+            vec![]
+        };
+
+        // Tangent and mode columns named but absent from the header are treated as missing
+        let find = |column: &CsvColumn, optional: bool| match column {
+            CsvColumn::Index(index) => Ok(Some(*index)),
+            CsvColumn::Name(name) => match header.iter().position(|header| header == name) {
+                Some(index) => Ok(Some(index)),
+                None if optional => Ok(None),
+                None => Err(ParseError::new(format!("no column named `{name}`"))),
+            },
+        };
+        let find_optional = |column: &Option<CsvColumn>| match column {
+            Some(column) => find(column, true),
+            None => Ok(None),
+        };
+
+        let x = find(&self.x, false)?.unwrap();
+        let y = find(&self.y, false)?.unwrap();
+        let left_tan = find_optional(&self.left_tan)?;
+        let right_tan = find_optional(&self.right_tan)?;
+        let left_mode = find_optional(&self.left_mode)?;
+        let right_mode = find_optional(&self.right_mode)?;
+
+        let mut keys = vec![];
+        for (line_number, line) in rows {
+            let cells = split_row(line, self.delimiter);
+            let cell = |index: usize| {
+                cells.get(index).copied().ok_or_else(|| {
+                    ParseError::at_line(line_number, format!("missing column {index}"))
+                })
+            };
+            let number = |index: usize| {
+                let text = cell(index)?;
+                text.parse::<f32>()
+                    .ok()
+                    .filter(|value| value.is_finite())
+                    .ok_or_else(|| {
+                        ParseError::at_line(line_number, format!("invalid number `{text}`"))
+                    })
+            };
+            let tangent = |index: Option<usize>| index.map(number).transpose();
+            let mode = |index: Option<usize>, tangent: Option<f32>| match index {
+                Some(index) => parse_mode(cell(index)?, line_number),
+                None if tangent.is_some() => Ok(TangentMode::Free),
+                None => Ok(TangentMode::Linear),
+            };
+
+            let (left, right) = (tangent(left_tan)?, tangent(right_tan)?);
+            keys.push((
+                pos2(number(x)?, number(y)?),
+                left,
+                right,
+                mode(left_mode, left)?,
+                mode(right_mode, right)?,
+            ));
+        }
+
+        let mut result = CsvCurve::default();
+        if self.fit_ranges && !keys.is_empty() {
+            let fit = |values: &mut dyn Iterator<Item = f32>| {
+                let mut range: Rangef = Rangef::NOTHING;
+                for value in values {
+                    range.min = range.min.min(value);
+                    range.max = range.max.max(value);
+                }
+                // Flat ranges still need a unit to normalize with
+                if range.span() <= 0.0 {
+                    range.max = range.min + 1.0;
+                }
+                range
+            };
+            result.x_range = fit(&mut keys.iter().map(|key| key.0.x));
+            result.y_range = fit(&mut keys.iter().map(|key| key.0.y));
+        }
+
+        let scale = result.x_range.span() / result.y_range.span();
+        result.curve = Curve::from_points(keys.into_iter().map(
+            |(pos, left_tan, right_tan, left_mode, right_mode)| {
+                Point::new(
+                    pos2(
+                        (pos.x - result.x_range.min) / result.x_range.span(),
+                        (pos.y - result.y_range.min) / result.y_range.span(),
+                    ),
+                    left_tan.unwrap_or(0.0) * scale,
+                    right_tan.unwrap_or(0.0) * scale,
+                    left_mode,
+                    right_mode,
+                )
+            },
+        ));
+
+        Ok(result)
+    }
+}
+
+#[allow(unused)]
+impl Curve {
+    pub fn to_csv_samples(&self, resolution: usize) -> String {
+        CsvCurve::new(self.clone()).to_csv_samples(resolution)
+    }
+
+    pub fn to_csv_keys(&self) -> String {
+        CsvCurve::new(self.clone()).to_csv_keys()
+    }
+
+    pub fn from_csv_keys(text: &str) -> Result<Self, ParseError> {
+        CsvImport::default().parse(text).map(|csv| csv.curve)
+    }
+}
+
+fn split_row(line: &str, delimiter: char) -> Vec<&str> {
+    line.split(delimiter)
+        .map(|cell| cell.trim().trim_matches('"').trim())
+        .collect()
+}
+
+fn parse_mode(text: &str, line_number: usize) -> Result<TangentMode, ParseError> {
+    match text.to_ascii_lowercase().as_str() {
+        "free" | "0" => Ok(TangentMode::Free),
+        "linear" | "1" => Ok(TangentMode::Linear),
+        _ => Err(ParseError::at_line(
+            line_number,
+            format!("invalid tangent mode `{text}`"),
+        )),
+    }
+}
+
+fn mode_to_str(mode: TangentMode) -> &'static str {
+    match mode {
+        TangentMode::Free => "free",
+        TangentMode::Linear => "linear",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn curve() -> Curve {
+        Curve::from_points([
+            Point::new(
+                pos2(0.0, 0.2),
+                0.0,
+                0.5,
+                TangentMode::Free,
+                TangentMode::Free,
+            ),
+            Point::new(
+                pos2(0.5, 1.0),
+                0.0,
+                0.0,
+                TangentMode::Linear,
+                TangentMode::Linear,
+            ),
+            Point::new(
+                pos2(1.0, 0.0),
+                -1.5,
+                0.0,
+                TangentMode::Free,
+                TangentMode::Free,
+            ),
+        ])
+    }
+
+    fn assert_same_keys(a: &Curve, b: &Curve) {
+        assert_eq!(a.points().len(), b.points().len());
+        for (a, b) in a.points().iter().zip(b.points()) {
+            assert!((a.pos() - b.pos()).length() < 0.0001);
+            assert!((a.left_tan() - b.left_tan()).abs() < 0.0001);
+            assert!((a.right_tan() - b.right_tan()).abs() < 0.0001);
+            assert!(a.left_mode() == b.left_mode());
+            assert!(a.right_mode() == b.right_mode());
+        }
+    }
+
+    #[test]
+    fn round_trips_keys() {
+        let curve = curve();
+        let text = curve.to_csv_keys();
+        assert!(text
+            .starts_with("x,y,left_tan,right_tan,left_mode,right_mode\n0,0.2,0,0.5,free,free\n"));
+
+        assert_same_keys(&Curve::from_csv_keys(&text).unwrap(), &curve);
+    }
+
+    #[test]
+    fn round_trips_keys_with_ranges() {
+        let csv = CsvCurve {
+            curve: curve(),
+            x_range: Rangef::new(0.0, 10.0),
+            y_range: Rangef::new(-1.0, 1.0),
+        };
+        let text = csv.to_csv_keys();
+        assert!(text.contains("\n10,-1,-0.3,0,free,free\n"));
+
+        let read = CsvImport::new().with_fit_ranges(true).parse(&text).unwrap();
+        assert_eq!(read.x_range, csv.x_range);
+        assert_eq!(read.y_range, csv.y_range);
+        assert_same_keys(&read.curve, &csv.curve);
+    }
+
+    #[test]
+    fn writes_samples() {
+        let csv = CsvCurve {
+            curve: curve(),
+            x_range: Rangef::new(0.0, 2.0),
+            y_range: Rangef::new(0.0, 10.0),
+        };
+        let text = csv.to_csv_samples(2);
+        let rows: Vec<&str> = text.lines().collect();
+        assert_eq!(rows, ["x,y", "0,2", "1,10", "2,0"]);
+
+        // A resolution of 0 still writes both ends
+        assert_eq!(csv.to_csv_samples(0).lines().count(), 3);
+        assert_eq!(Curve::default().to_csv_samples(1), "x,y\n0,0\n1,0\n");
+    }
+
+    #[test]
+    fn reads_columns_without_header() {
+        let text = "# time; value\n0; 0\n\n0.5; 1\n1; 0.5\n";
+        let curve = CsvImport::new()
+            .with_header(false)
+            .with_x(0)
+            .with_y(1)
+            .with_left_tan(None)
+            .with_right_tan(None)
+            .with_left_mode(None)
+            .with_right_mode(None)
+            .with_delimiter(';')
+            .parse(text)
+            .unwrap()
+            .curve;
+
+        assert_eq!(curve.points().len(), 3);
+        assert!(curve
+            .points()
+            .iter()
+            .all(|point| point.left_mode() == TangentMode::Linear));
+        assert!((curve.sample(0.25) - 0.5).abs() < 0.0001);
+    }
+
+    #[test]
+    fn reads_named_columns_in_any_order() {
+        let text = "\"value\",\"time\",\"unused\"\n1,0,a\n0,1,b\n";
+        let read = CsvImport::new()
+            .with_x("time")
+            .with_y("value")
+            .parse(text)
+            .unwrap();
+
+        // Missing tangent columns give linear keys
+        assert!((read.curve.sample(0.25) - 0.75).abs() < 0.0001);
+    }
+
+    #[test]
+    fn clamps_or_fits_values() {
+        let text = "x,y\n-1,5\n3,-5\n";
+        let clamped = Curve::from_csv_keys(text).unwrap();
+        assert_eq!(clamped.get_position(0), Some(pos2(0.0, 1.0)));
+        assert_eq!(clamped.get_position(1), Some(pos2(1.0, 0.0)));
+
+        let fitted = CsvImport::new().with_fit_ranges(true).parse(text).unwrap();
+        assert_eq!(fitted.x_range, Rangef::new(-1.0, 3.0));
+        assert_eq!(fitted.y_range, Rangef::new(-5.0, 5.0));
+
+        // A single key gets a unit range
+        let single = CsvImport::new()
+            .with_fit_ranges(true)
+            .parse("x,y\n2,3\n")
+            .unwrap();
+        assert_eq!(single.x_range, Rangef::new(2.0, 3.0));
+        assert_eq!(single.curve.get_position(0), Some(pos2(0.0, 0.0)));
+
+        let empty = CsvImport::new()
+            .with_fit_ranges(true)
+            .parse("x,y\n")
+            .unwrap();
+        assert!(empty.curve.points().is_empty());
+        assert_eq!(empty.x_range, Rangef::new(0.0, 1.0));
+    }
+
+    #[test]
+    fn reports_errors_with_lines() {
+        let error = |text: &str| Curve::from_csv_keys(text).err().unwrap();
+
+        assert_eq!(error("").line, None);
+        assert_eq!(error("a,b\n0,0\n").line, None);
+        assert_eq!(error("x,y\n0,0\n0.5\n").line, Some(3));
+        assert_eq!(error("x,y\n0,zero\n").line, Some(2));
+        assert_eq!(error("x,y\n0,inf\n").line, Some(2));
+        assert_eq!(
+            error("x,y,left_tan,left_mode\n0,0,0,smooth\n").line,
+            Some(2)
+        );
+    }
+}
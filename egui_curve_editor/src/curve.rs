@@ -1,5 +1,9 @@
 // From Godot
 
+use crate::math::{pos2, vec2, Float, Pos2, Rangef, Rect, Vec2};
+use crate::prelude::*;
+use crate::CurveError;
+
 #[derive(PartialEq, Clone, Copy, Default, serde::Deserialize, serde::Serialize)]
 pub enum TangentMode {
     Free,
@@ -7,51 +11,187 @@ pub enum TangentMode {
     Linear,
 }
 
+/// A point of a [`Curve`], or of a [`CurveT`] over another [`Float`].
+pub type Point = PointT<f32>;
+
 #[derive(Clone, Copy, Default, serde::Deserialize, serde::Serialize)]
-pub struct Point {
-    pos: egui::Pos2,
-    left_tan: f32,
-    right_tan: f32,
+pub struct PointT<S> {
+    pos: Pos2<S>,
+    left_tan: S,
+    right_tan: S,
     left_mode: TangentMode,
     right_mode: TangentMode,
 }
 
-impl Point {
-    pub fn from_pos(pos: egui::Pos2) -> Self {
+#[allow(unused)]
+impl<S: Float> PointT<S> {
+    pub fn new(
+        pos: impl Into<Pos2<S>>,
+        left_tan: S,
+        right_tan: S,
+        left_mode: TangentMode,
+        right_mode: TangentMode,
+    ) -> Self {
         Self {
-            pos,
+            pos: pos.into(),
+            left_tan,
+            right_tan,
+            left_mode,
+            right_mode,
+        }
+    }
+
+    pub fn from_pos(pos: impl Into<Pos2<S>>) -> Self {
+        Self {
+            pos: pos.into(),
             ..Default::default()
         }
     }
+
+    pub fn pos(&self) -> Pos2<S> {
+        self.pos
+    }
+
+    pub fn left_tan(&self) -> S {
+        self.left_tan
+    }
+
+    pub fn right_tan(&self) -> S {
+        self.right_tan
+    }
+
+    pub fn left_mode(&self) -> TangentMode {
+        self.left_mode
+    }
+
+    pub fn right_mode(&self) -> TangentMode {
+        self.right_mode
+    }
+
+    /// Converts to another precision, rounding to the nearest value.
+    pub fn cast<T: Float>(&self) -> PointT<T> {
+        PointT::new(
+            self.pos.cast(),
+            T::from_f64(self.left_tan.to_f64()),
+            T::from_f64(self.right_tan.to_f64()),
+            self.left_mode,
+            self.right_mode,
+        )
+    }
 }
 
+/// A curve through points in `0..=1`, with cubic segments shaped by the tangents of their ends.
+pub type Curve = CurveT<f32>;
+
+/// The model of [`Curve`] over any [`Float`], see [`Curve64`](crate::Curve64) for `f64`.
+///
+/// Points lie in `0..=1`, and so do sampled values: where tangents make a segment overshoot past
+/// `0` or `1`, [`CurveT::sample`] clamps it. The analysis, from [`CurveT::solve_for_x`] to
+/// [`CurveT::overshoot_ranges`], describes the clamped curve as it is sampled.
+///
+/// Serialized as [`CurveData`](crate::CurveData), older formats are migrated when read.
 #[derive(Clone, Default, serde::Deserialize, serde::Serialize)]
-pub struct Curve {
-    points: Vec<Point>,
+#[serde(
+    into = "crate::CurveData<S>",
+    try_from = "crate::AnyCurveData<S>",
+    bound = "S: Float"
+)]
+pub struct CurveT<S> {
+    points: Vec<PointT<S>>,
+    monotone: bool,
 }
 
 #[allow(unused)]
-impl Curve {
+impl<S: Float> CurveT<S> {
     pub fn linear() -> Self {
         Self {
             points: vec![
-                Point::from_pos(egui::pos2(0.0, 0.0)),
-                Point::from_pos(egui::pos2(1.0, 1.0)),
+                PointT::from_pos(Pos2::new(S::ZERO, S::ZERO)),
+                PointT::from_pos(Pos2::new(S::ONE, S::ONE)),
             ],
+            ..Default::default()
         }
     }
 
-    pub fn add_point(&mut self, mut point: Point) -> usize {
-        point.pos = point.pos.clamp(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+    /// Builds a curve from points in any order, linear tangents are recomputed.
+    ///
+    /// Points sharing an x keep their order, which makes a jump between them.
+    pub fn from_points(points: impl IntoIterator<Item = PointT<S>>) -> Self {
+        let mut points: Vec<PointT<S>> = points
+            .into_iter()
+            .filter(|point| {
+                [point.pos.x, point.pos.y, point.left_tan, point.right_tan]
+                    .iter()
+                    .all(|value| value.is_finite())
+            })
+            .map(|mut point| {
+                point.pos = point.pos.clamp(Pos2::ZERO, Pos2::new(S::ONE, S::ONE));
+                point
+            })
+            .collect();
+        points.sort_by(|a, b| a.pos.x.total_cmp(&b.pos.x));
+
+        let mut curve = Self {
+            points,
+            ..Default::default()
+        };
+        for index in 0..curve.points.len() {
+            curve.update_auto_tangents(index);
+        }
+
+        curve
+    }
+
+    /// Converts to another precision, rounding to the nearest value.
+    pub fn cast<T: Float>(&self) -> CurveT<T> {
+        let mut curve = CurveT::from_points(self.points.iter().map(PointT::cast));
+        curve.set_monotone_constraint(self.monotone);
+
+        curve
+    }
+
+    pub fn points(&self) -> &[PointT<S>] {
+        &self.points
+    }
+
+    pub fn monotone_constraint(&self) -> bool {
+        self.monotone
+    }
+
+    /// Limits tangents so that every segment stays monotone between its points.
+    ///
+    /// Enabling the constraint immediately limits the current tangents.
+    pub fn set_monotone_constraint(&mut self, monotone: bool) {
+        self.monotone = monotone;
+
+        for index in 0..self.points.len().saturating_sub(1) {
+            self.limit_tangents(index);
+        }
+    }
+
+    /// Returns the index of the new point, non-finite points are ignored and give `0`.
+    ///
+    /// That `0` cannot be told apart from a point inserted first, see [`CurveT::try_add_point`].
+    pub fn add_point(&mut self, point: PointT<S>) -> usize {
+        self.try_add_point(point).unwrap_or(0)
+    }
+
+    pub fn try_add_point(&mut self, mut point: PointT<S>) -> Result<usize, CurveError<S>> {
+        check_finite(point.pos.x)?;
+        check_finite(point.pos.y)?;
+        check_finite(point.left_tan)?;
+        check_finite(point.right_tan)?;
 
-        let index = if self.points.len() == 0 {
+        point.pos = point.pos.clamp(Pos2::ZERO, Pos2::new(S::ONE, S::ONE));
+
+        let index = if self.points.is_empty() {
             self.points.push(point);
 
             0
         } else if self.points.len() == 1 {
             let diff = point.pos.x - self.points[0].pos.x;
 
-            if diff > 0.0 {
+            if diff > S::ZERO {
                 self.points.push(point);
 
                 1
@@ -76,22 +216,41 @@ impl Curve {
 
         self.update_auto_tangents(index);
 
-        index
+        Ok(index)
     }
 
     pub fn remove_point(&mut self, index: usize) {
-        if index > self.points.len() - 1 {
-            return;
+        let _ = self.try_remove_point(index);
+    }
+
+    pub fn try_remove_point(&mut self, index: usize) -> Result<PointT<S>, CurveError<S>> {
+        self.check_index(index)?;
+
+        let point = self.points.remove(index);
+
+        if index > 0 && index < self.points.len() {
+            self.limit_tangents(index - 1);
         }
 
-        self.points.remove(index);
+        Ok(point)
     }
 
     pub fn clear_points(&mut self) {
         self.points.clear();
     }
 
-    pub fn get_index(&self, offset: f32) -> usize {
+    /// Returns the index of the segment containing `offset`, or `0` on an empty curve.
+    pub fn get_index(&self, offset: S) -> usize {
+        self.try_get_index(offset).unwrap_or(0)
+    }
+
+    pub fn try_get_index(&self, offset: S) -> Result<usize, CurveError<S>> {
+        check_finite(offset)?;
+
+        if self.points.is_empty() {
+            return Err(CurveError::Empty);
+        }
+
         let mut min = 0;
         let mut max = self.points.len() - 1;
 
@@ -106,46 +265,176 @@ impl Curve {
             } else if a > offset {
                 max = m;
             } else {
-                return m;
+                return Ok(m);
             }
         }
 
         if offset > self.points[max].pos.x {
-            return max;
+            return Ok(max);
         }
 
-        min
+        Ok(min)
     }
 
-    pub fn sample(&self, offset: f32) -> f32 {
-        if self.points.len() == 0 {
-            return 0.0;
+    /// Returns the value at `offset`, clamped to `0..=1`, or `0` on an empty curve.
+    ///
+    /// A non-finite offset gives the value of the first point.
+    pub fn sample(&self, offset: S) -> S {
+        if !offset.is_finite() {
+            return self.points.first().map_or(S::ZERO, |point| point.pos.y);
         }
 
+        self.try_sample(offset).unwrap_or(S::ZERO)
+    }
+
+    pub fn try_sample(&self, offset: S) -> Result<S, CurveError<S>> {
+        let i = self.try_get_index(offset)?;
+
         if self.points.len() == 1 {
-            return self.points[0].pos.y;
+            return Ok(self.points[0].pos.y);
         }
 
-        let i = self.get_index(offset);
-
-        if i == self.points.len() - 1 {
-            return self.points[i].pos.y;
+        // Also covers a jump on the last point
+        let last = self.points.len() - 1;
+        if i == last || offset >= self.points[last].pos.x {
+            return Ok(self.points[last].pos.y);
         }
 
         let local = offset - self.points[i].pos.x;
 
-        if i == 0 && local <= 0.0 {
-            return self.points[0].pos.y;
+        if i == 0 && local <= S::ZERO {
+            return Ok(self.points[0].pos.y);
+        }
+
+        Ok(self.sample_local_nocheck(i, local))
+    }
+
+    /// Finds every offset at which the curve reaches `value`, in ascending order.
+    ///
+    /// Segments that are flat at `value` each report their start offset. Values outside `0..=1`
+    /// are never sampled and have no offsets, while `0` and `1` are reached at the edges of a
+    /// clamped overshoot.
+    pub fn solve_for_x(&self, value: S) -> Vec<S> {
+        let mut roots = vec![];
+        if !(S::ZERO..=S::ONE).contains(&value) {
+            return roots;
+        }
+
+        if self.points.len() == 1 {
+            if (self.points[0].pos.y - value).abs() < S::TOLERANCE {
+                roots.push(self.points[0].pos.x);
+            }
+
+            return roots;
+        }
+
+        for index in 0..self.points.len().saturating_sub(1) {
+            for x in self.solve_segment_nocheck(index, value) {
+                if roots.last().is_none_or(|&last: &S| x - last > S::TOLERANCE) {
+                    roots.push(x);
+                }
+            }
+        }
+
+        roots
+    }
+
+    pub fn first_x_at(&self, value: S) -> Option<S> {
+        self.solve_for_x(value).first().copied()
+    }
+
+    /// Returns the true range of values of the curve, including overshoot caused by tangents.
+    ///
+    /// Like sampled values, the range does not leave `0..=1`.
+    pub fn value_range(&self) -> Option<Rangef<S>> {
+        let first = self.points.first()?;
+
+        let mut range = Rangef::point(first.pos.y);
+        for index in 0..self.points.len() - 1 {
+            for value in self.segment_extrema_nocheck(index) {
+                range.min = range.min.min(value);
+                range.max = range.max.max(value);
+            }
+        }
+
+        Some(range)
+    }
+
+    /// Returns the bounding box of the curve between its first and last points.
+    pub fn bounds(&self) -> Option<Rect<S>> {
+        let x = Rangef::new(self.points.first()?.pos.x, self.points.last()?.pos.x);
+
+        Some(Rect::from_x_y_ranges(x, self.value_range()?))
+    }
+
+    /// Whether the curve never changes direction, ignoring flat parts.
+    pub fn is_monotonic(&self) -> bool {
+        let mut increasing = false;
+        let mut decreasing = false;
+        for index in 0..self.points.len().saturating_sub(1) {
+            for values in self.segment_extrema_nocheck(index).windows(2) {
+                increasing |= values[1] - values[0] > S::TOLERANCE;
+                decreasing |= values[0] - values[1] > S::TOLERANCE;
+            }
         }
 
-        self.sample_local_nocheck(i, local)
+        !(increasing && decreasing)
     }
 
-    pub fn point_positions(&self) -> Vec<egui::Pos2> {
+    /// Returns the four control points of the bézier between points `index` and `index + 1`.
+    pub fn segment_bezier(&self, index: usize) -> Option<[Pos2<S>; 4]> {
+        if index + 1 >= self.points.len() {
+            return None;
+        }
+
+        let a = self.points[index].pos;
+        let b = self.points[index + 1].pos;
+        let d = (b.x - a.x) / S::from_f32(3.0);
+        let [_, yac, ybc, _] = self.segment_bezier_nocheck(index);
+
+        Some([a, Pos2::new(a.x + d, yac), Pos2::new(b.x - d, ybc), b])
+    }
+
+    /// Finds the x ranges where a segment leaves the range of values spanned by its two points.
+    ///
+    /// Overshoot that is clamped away at `0` or `1` does not count.
+    pub fn overshoot_ranges(&self) -> Vec<Rangef<S>> {
+        let half = S::from_f32(0.5);
+
+        let mut ranges = vec![];
+        for index in 0..self.points.len().saturating_sub(1) {
+            let [ya, yac, ybc, yb] = self.segment_bezier_nocheck(index);
+            let (low, high) = (ya.min(yb), ya.max(yb));
+
+            let a = self.points[index].pos.x;
+            let d = self.points[index + 1].pos.x - a;
+            if d.abs() < S::TOLERANCE {
+                continue;
+            }
+
+            let mut bounds = vec![S::ZERO, S::ONE];
+            bounds.extend(bezier_solve(ya, yac, ybc, yb, low));
+            bounds.extend(bezier_solve(ya, yac, ybc, yb, high));
+            bounds.sort_by(S::total_cmp);
+
+            for t in bounds.windows(2) {
+                let y = bezier_interpolate(ya, yac, ybc, yb, (t[0] + t[1]) * half)
+                    .clamp(S::ZERO, S::ONE);
+                if t[1] - t[0] > S::TOLERANCE && (y < low - S::TOLERANCE || y > high + S::TOLERANCE)
+                {
+                    ranges.push(Rangef::new(a + t[0] * d, a + t[1] * d));
+                }
+            }
+        }
+
+        ranges
+    }
+
+    pub fn point_positions(&self) -> Vec<Pos2<S>> {
         self.points.iter().map(|point| point.pos).collect()
     }
 
-    pub fn get_position(&self, index: usize) -> Option<egui::Pos2> {
+    pub fn get_position(&self, index: usize) -> Option<Pos2<S>> {
         if index >= self.points.len() {
             return None;
         }
@@ -153,27 +442,47 @@ impl Curve {
         Some(self.points[index].pos)
     }
 
-    pub fn set_position(&mut self, index: usize, mut pos: egui::Pos2) {
-        pos = pos.clamp(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+    pub fn set_position(&mut self, index: usize, pos: impl Into<Pos2<S>>) {
+        let _ = self.try_set_position(index, pos);
+    }
 
-        if index >= self.points.len() {
-            return;
-        }
+    pub fn try_set_position(
+        &mut self,
+        index: usize,
+        pos: impl Into<Pos2<S>>,
+    ) -> Result<(), CurveError<S>> {
+        let mut pos = pos.into();
+        check_finite(pos.x)?;
+        check_finite(pos.y)?;
+
+        pos = pos.clamp(Pos2::ZERO, Pos2::new(S::ONE, S::ONE));
+
+        self.check_index(index)?;
 
         if index > 0 && self.points[index - 1].pos.x > pos.x {
-            return;
+            return Err(CurveError::CrossesNeighbor {
+                index,
+                neighbor: index - 1,
+                x: pos.x,
+            });
         }
 
         if index < self.points.len() - 1 && self.points[index + 1].pos.x < pos.x {
-            return;
+            return Err(CurveError::CrossesNeighbor {
+                index,
+                neighbor: index + 1,
+                x: pos.x,
+            });
         }
 
         self.points[index].pos = pos;
         //TODO:
         self.update_auto_tangents(index);
+
+        Ok(())
     }
 
-    pub fn get_left_tan(&self, index: usize) -> Option<f32> {
+    pub fn get_left_tan(&self, index: usize) -> Option<S> {
         if index >= self.points.len() {
             return None;
         }
@@ -181,16 +490,25 @@ impl Curve {
         Some(self.points[index].left_tan)
     }
 
-    pub fn set_left_tan(&mut self, index: usize, tangent: f32) {
-        if index >= self.points.len() || tangent.is_nan() || tangent.is_infinite() {
-            return;
-        }
+    pub fn set_left_tan(&mut self, index: usize, tangent: S) {
+        let _ = self.try_set_left_tan(index, tangent);
+    }
+
+    pub fn try_set_left_tan(&mut self, index: usize, tangent: S) -> Result<(), CurveError<S>> {
+        self.check_index(index)?;
+        check_finite(tangent)?;
 
         self.points[index].left_tan = tangent;
         self.points[index].left_mode = TangentMode::Free;
+
+        if index > 0 {
+            self.limit_tangents(index - 1);
+        }
+
+        Ok(())
     }
 
-    pub fn get_right_tan(&self, index: usize) -> Option<f32> {
+    pub fn get_right_tan(&self, index: usize) -> Option<S> {
         if index >= self.points.len() {
             return None;
         }
@@ -198,17 +516,26 @@ impl Curve {
         Some(self.points[index].right_tan)
     }
 
-    pub fn set_right_tan(&mut self, index: usize, tangent: f32) {
-        if index >= self.points.len() || tangent.is_nan() || tangent.is_infinite() {
-            return;
-        }
+    pub fn set_right_tan(&mut self, index: usize, tangent: S) {
+        let _ = self.try_set_right_tan(index, tangent);
+    }
+
+    pub fn try_set_right_tan(&mut self, index: usize, tangent: S) -> Result<(), CurveError<S>> {
+        self.check_index(index)?;
+        check_finite(tangent)?;
 
         self.points[index].right_tan = tangent;
         self.points[index].right_mode = TangentMode::Free;
+
+        if index + 1 < self.points.len() {
+            self.limit_tangents(index);
+        }
+
+        Ok(())
     }
 
     pub fn index_is_first_or_last(&self, index: usize) -> bool {
-        index == 0 || index == self.points.len() - 1
+        index == 0 || index + 1 == self.points.len()
     }
 
     pub fn index_is_first(&self, index: usize) -> bool {
@@ -216,10 +543,21 @@ impl Curve {
     }
 
     pub fn index_is_last(&self, index: usize) -> bool {
-        index == self.points.len() - 1
+        index + 1 == self.points.len()
+    }
+
+    fn check_index(&self, index: usize) -> Result<(), CurveError<S>> {
+        if index >= self.points.len() {
+            return Err(CurveError::IndexOutOfBounds {
+                index,
+                len: self.points.len(),
+            });
+        }
+
+        Ok(())
     }
 
-    fn sample_local_nocheck(&self, index: usize, mut local_offset: f32) -> f32 {
+    fn sample_local_nocheck(&self, index: usize, mut local_offset: S) -> S {
         let a = self.points[index];
         let b = self.points[index + 1];
 
@@ -227,18 +565,68 @@ impl Curve {
 
         // Control points at equal distances
         let mut d = b.pos.x - a.pos.x;
-        const EPSILON: f32 = 0.00001;
-        if d.abs() < EPSILON {
+        if d.abs() < S::TOLERANCE {
             return b.pos.y;
         }
         local_offset /= d;
-        d /= 3.0;
+        d /= S::from_f32(3.0);
         let yac = a.pos.y + d * a.right_tan;
         let ybc = b.pos.y - d * b.left_tan;
 
         let y = bezier_interpolate(a.pos.y, yac, ybc, b.pos.y, local_offset);
 
-        y.clamp(0.0, 1.0)
+        y.clamp(S::ZERO, S::ONE)
+    }
+
+    fn solve_segment_nocheck(&self, index: usize, value: S) -> Vec<S> {
+        let a = self.points[index];
+        let b = self.points[index + 1];
+
+        let d = b.pos.x - a.pos.x;
+        if d.abs() < S::TOLERANCE {
+            // Vertical jump, the value is reached if it lies between both ends
+            let (low, high) = (a.pos.y.min(b.pos.y), a.pos.y.max(b.pos.y));
+            if low - S::TOLERANCE <= value && value <= high + S::TOLERANCE {
+                return vec![a.pos.x];
+            }
+
+            return vec![];
+        }
+
+        let [ya, yac, ybc, yb] = self.segment_bezier_nocheck(index);
+
+        bezier_solve(ya, yac, ybc, yb, value)
+            .into_iter()
+            .map(|t| a.pos.x + t * d)
+            .collect()
+    }
+
+    /// Values reached inside a segment, at its ends and its local extrema, clamped like samples.
+    fn segment_extrema_nocheck(&self, index: usize) -> Vec<S> {
+        let [ya, yac, ybc, yb] = self.segment_bezier_nocheck(index);
+
+        let mut values = vec![ya];
+        values.extend(
+            bezier_extrema(ya, yac, ybc, yb)
+                .into_iter()
+                .map(|t| bezier_interpolate(ya, yac, ybc, yb, t).clamp(S::ZERO, S::ONE)),
+        );
+        values.push(yb);
+
+        values
+    }
+
+    /// Returns the y of the four bézier control points of a segment.
+    fn segment_bezier_nocheck(&self, index: usize) -> [S; 4] {
+        let a = self.points[index];
+        let b = self.points[index + 1];
+
+        // Control points at equal distances
+        let d = (b.pos.x - a.pos.x) / S::from_f32(3.0);
+        let yac = a.pos.y + d * a.right_tan;
+        let ybc = b.pos.y - d * b.left_tan;
+
+        [a.pos.y, yac, ybc, b.pos.y]
     }
 
     fn update_auto_tangents(&mut self, index: usize) {
@@ -247,36 +635,694 @@ impl Curve {
         if index > 0 {
             if p.left_mode == TangentMode::Linear {
                 let v = (self.points[index - 1].pos - p.pos).normalized();
-                p.left_tan = v.y / v.x;
+                p.left_tan = linear_tangent(v);
             }
             if self.points[index - 1].right_mode == TangentMode::Linear {
                 let v = (self.points[index - 1].pos - p.pos).normalized();
-                self.points[index - 1].right_tan = v.y / v.x;
+                self.points[index - 1].right_tan = linear_tangent(v);
             }
         }
 
         if index + 1 < self.points.len() {
             if p.right_mode == TangentMode::Linear {
                 let v = (self.points[index + 1].pos - p.pos).normalized();
-                p.right_tan = v.y / v.x;
+                p.right_tan = linear_tangent(v);
             }
             if self.points[index + 1].left_mode == TangentMode::Linear {
                 let v = (self.points[index + 1].pos - p.pos).normalized();
-                self.points[index + 1].left_tan = v.y / v.x;
+                self.points[index + 1].left_tan = linear_tangent(v);
             }
         }
 
         self.points[index] = p;
+
+        if index > 0 {
+            self.limit_tangents(index - 1);
+        }
+        if index + 1 < self.points.len() {
+            self.limit_tangents(index);
+        }
+    }
+
+    /// Fritsch–Carlson limiting of the tangents of a segment, if the monotone constraint is on.
+    fn limit_tangents(&mut self, index: usize) {
+        if !self.monotone {
+            return;
+        }
+
+        let a = self.points[index].pos;
+        let b = self.points[index + 1].pos;
+
+        let d = b.x - a.x;
+        if d.abs() < S::TOLERANCE {
+            return;
+        }
+
+        let secant = (b.y - a.y) / d;
+        if secant.abs() < S::TOLERANCE {
+            self.points[index].right_tan = S::ZERO;
+            self.points[index + 1].left_tan = S::ZERO;
+
+            return;
+        }
+
+        // Tangents going against the secant would overshoot
+        let alpha = (self.points[index].right_tan / secant).max(S::ZERO);
+        let beta = (self.points[index + 1].left_tan / secant).max(S::ZERO);
+
+        let three = S::from_f32(3.0);
+        let length = alpha.hypot(beta);
+        let scale = if length > three {
+            three / length
+        } else {
+            S::ONE
+        };
+
+        self.points[index].right_tan = alpha * scale * secant;
+        self.points[index + 1].left_tan = beta * scale * secant;
+    }
+}
+
+fn check_finite<S: Float>(value: S) -> Result<(), CurveError<S>> {
+    if !value.is_finite() {
+        return Err(CurveError::NonFinite(value));
     }
+
+    Ok(())
 }
 
-fn bezier_interpolate(start: f32, control_1: f32, control_2: f32, end: f32, t: f32) -> f32 {
+fn linear_tangent<S: Float>(direction: Vec2<S>) -> S {
+    // Points sharing an x make a jump, which has no slope to follow
+    if direction.x.abs() < S::TOLERANCE {
+        return S::ZERO;
+    }
+
+    direction.y / direction.x
+}
+
+pub(crate) fn bezier_interpolate<S: Float>(
+    start: S,
+    control_1: S,
+    control_2: S,
+    end: S,
+    t: S,
+) -> S {
     // From Wikipedia
-    let omt = 1.0 - t;
+    let three = S::from_f32(3.0);
+    let omt = S::ONE - t;
     let omt2 = omt * omt;
     let omt3 = omt2 * omt;
     let t2 = t * t;
     let t3 = t2 * t;
 
-    start * omt3 + control_1 * omt2 * t * 3.0 + control_2 * omt * t2 * 3.0 + end * t3
+    start * omt3 + control_1 * omt2 * t * three + control_2 * omt * t2 * three + end * t3
+}
+
+pub(crate) fn bezier_derivative<S: Float>(start: S, control_1: S, control_2: S, end: S, t: S) -> S {
+    let omt = S::ONE - t;
+
+    S::from_f32(3.0)
+        * ((control_1 - start) * omt * omt
+            + S::from_f32(2.0) * (control_2 - control_1) * omt * t
+            + (end - control_2) * t * t)
+}
+
+/// Splits a 2D bézier going forward in x into points whose segments follow it within `tolerance`.
+///
+/// `Curve` keeps its control points at thirds, so a bézier with other control points needs
+/// several segments. Points carry free tangents matching the direction of the bézier.
+pub(crate) fn bezier_to_points(bezier: [Pos2; 4], tolerance: f32) -> Vec<Point> {
+    const MAX_DEPTH: u32 = 6;
+    const MAX_TANGENT: f32 = 100.0;
+    const EPSILON: f32 = 0.00001;
+
+    let [p0, p1, p2, p3] = bezier;
+    let at = |s: f32| {
+        pos2(
+            bezier_interpolate(p0.x, p1.x, p2.x, p3.x, s),
+            bezier_interpolate(p0.y, p1.y, p2.y, p3.y, s),
+        )
+    };
+    let tangent = |s: f32| {
+        let mut direction = vec2(
+            bezier_derivative(p0.x, p1.x, p2.x, p3.x, s),
+            bezier_derivative(p0.y, p1.y, p2.y, p3.y, s),
+        );
+        if direction.length() < EPSILON {
+            // Coincident control points, look a little further along
+            direction = if s < 0.5 {
+                at(s + 0.001) - at(s)
+            } else {
+                at(s) - at(s - 0.001)
+            };
+        }
+
+        if direction.x.abs() < EPSILON {
+            MAX_TANGENT * direction.y.signum()
+        } else {
+            (direction.y / direction.x).clamp(-MAX_TANGENT, MAX_TANGENT)
+        }
+    };
+
+    // Largest distance between the bézier and the segment built from its ends
+    let error = |s0: f32, s1: f32| {
+        let (a, b) = (at(s0), at(s1));
+        let d = b.x - a.x;
+        if d < EPSILON {
+            return 0.0;
+        }
+
+        let yac = a.y + d / 3.0 * tangent(s0);
+        let ybc = b.y - d / 3.0 * tangent(s1);
+        (1..8)
+            .map(|i| {
+                let p = at(s0 + (s1 - s0) * i as f32 / 8.0);
+                let t = (p.x - a.x) / d;
+                (bezier_interpolate(a.y, yac, ybc, b.y, t) - p.y).abs()
+            })
+            .fold(0.0, f32::max)
+    };
+
+    let mut params = vec![0.0];
+    let mut pending = vec![(0.0, 1.0, MAX_DEPTH)];
+    while let Some((s0, s1, depth)) = pending.pop() {
+        if depth > 0 && error(s0, s1) > tolerance {
+            let mid = (s0 + s1) * 0.5;
+            pending.push((mid, s1, depth - 1));
+            pending.push((s0, mid, depth - 1));
+        } else {
+            params.push(s1);
+        }
+    }
+
+    params
+        .into_iter()
+        .map(|s| {
+            let tangent = tangent(s);
+            Point::new(
+                at(s),
+                tangent,
+                tangent,
+                TangentMode::Free,
+                TangentMode::Free,
+            )
+        })
+        .collect()
+}
+
+/// Finds the parameters `t` in `0..=1` at which the bézier reaches `value`, in ascending order.
+///
+/// A flat bézier lying on `value` reports `t = 0` only.
+fn bezier_solve<S: Float>(start: S, control_1: S, control_2: S, end: S, value: S) -> Vec<S> {
+    const EPSILON: f64 = 0.000001;
+
+    let (p0, p1, p2, p3) = (
+        start.to_f64(),
+        control_1.to_f64(),
+        control_2.to_f64(),
+        end.to_f64(),
+    );
+
+    // Power basis of the bézier, shifted by the value we are looking for
+    let a = -p0 + 3.0 * p1 - 3.0 * p2 + p3;
+    let b = 3.0 * p0 - 6.0 * p1 + 3.0 * p2;
+    let c = -3.0 * p0 + 3.0 * p1;
+    let d = p0 - value.to_f64();
+
+    if a.abs() < EPSILON && b.abs() < EPSILON && c.abs() < EPSILON {
+        return if d.abs() < EPSILON {
+            vec![S::ZERO]
+        } else {
+            vec![]
+        };
+    }
+
+    let mut roots: Vec<f64> = solve_cubic(a, b, c, d)
+        .into_iter()
+        .map(|mut t| {
+            // Polish with Newton, the closed forms lose precision near multiple roots
+            for _ in 0..2 {
+                let f = ((a * t + b) * t + c) * t + d;
+                let df = (3.0 * a * t + 2.0 * b) * t + c;
+                if df.abs() > EPSILON {
+                    t -= f / df;
+                }
+            }
+            t
+        })
+        .filter(|t| (-EPSILON..=1.0 + EPSILON).contains(t))
+        .map(|t| t.clamp(0.0, 1.0))
+        .collect();
+
+    roots.sort_by(f64::total_cmp);
+    roots.dedup_by(|a, b| (*a - *b).abs() < EPSILON);
+
+    roots.into_iter().map(S::from_f64).collect()
+}
+
+/// Finds the parameters `t` in `0..1` at which the derivative of the bézier is zero.
+fn bezier_extrema<S: Float>(start: S, control_1: S, control_2: S, end: S) -> Vec<S> {
+    // The derivative is a quadratic bézier over the differences of the control points
+    let a = (control_1 - start).to_f64();
+    let b = (control_2 - control_1).to_f64();
+    let c = (end - control_2).to_f64();
+
+    let mut extrema: Vec<f64> = solve_quadratic(a - 2.0 * b + c, 2.0 * (b - a), a)
+        .into_iter()
+        .filter(|t| *t > 0.0 && *t < 1.0)
+        .collect();
+
+    extrema.sort_by(f64::total_cmp);
+
+    extrema.into_iter().map(S::from_f64).collect()
+}
+
+/// Real roots of `a * t^3 + b * t^2 + c * t + d`, degrading to lower orders when needed.
+fn solve_cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    const EPSILON: f64 = 0.000000001;
+
+    let scale = a.abs().max(b.abs()).max(c.abs()).max(d.abs());
+    if a.abs() <= EPSILON * scale {
+        return solve_quadratic(b, c, d);
+    }
+
+    // Depressed cubic u^3 + p * u + q with t = u - b / 3a
+    let (b, c, d) = (b / a, c / a, d / a);
+    let shift = -b / 3.0;
+    let p = c - b * b / 3.0;
+    let q = 2.0 * b * b * b / 27.0 - b * c / 3.0 + d;
+
+    let discriminant = q * q / 4.0 + p * p * p / 27.0;
+
+    if p.abs() < EPSILON {
+        vec![(-q).cbrt() + shift]
+    } else if discriminant > EPSILON {
+        let sqrt = discriminant.sqrt();
+        vec![(-q / 2.0 + sqrt).cbrt() + (-q / 2.0 - sqrt).cbrt() + shift]
+    } else if discriminant < -EPSILON {
+        let r = 2.0 * (-p / 3.0).sqrt();
+        let phi = (3.0 * q / (p * r)).clamp(-1.0, 1.0).acos() / 3.0;
+        (0..3)
+            .map(|k| r * (phi - 2.0 * core::f64::consts::PI * k as f64 / 3.0).cos() + shift)
+            .collect()
+    } else {
+        vec![3.0 * q / p + shift, -3.0 * q / (2.0 * p) + shift]
+    }
+}
+
+fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    const EPSILON: f64 = 0.000000001;
+
+    let scale = a.abs().max(b.abs()).max(c.abs());
+    if a.abs() <= EPSILON * scale {
+        if b.abs() <= EPSILON * scale {
+            return vec![];
+        }
+
+        return vec![-c / b];
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return vec![];
+    }
+
+    // Avoid cancellation between -b and the square root
+    let sqrt = discriminant.sqrt();
+    let q = -0.5 * (b + b.signum() * sqrt);
+    if q.abs() <= EPSILON * scale {
+        return vec![-b / (2.0 * a)];
+    }
+
+    vec![q / a, c / q]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn free(x: f32, y: f32, tangent: f32) -> Point {
+        Point::new(
+            pos2(x, y),
+            tangent,
+            tangent,
+            TangentMode::Free,
+            TangentMode::Free,
+        )
+    }
+
+    fn line() -> Curve {
+        Curve::from_points([
+            Point::from_pos(pos2(0.0, 0.0)),
+            Point::from_pos(pos2(1.0, 1.0)),
+        ])
+    }
+
+    fn assert_close(actual: &[f32], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len(), "{actual:?} != {expected:?}");
+        for (a, e) in actual.iter().zip(expected) {
+            assert!((a - e).abs() < 0.0001, "{actual:?} != {expected:?}");
+        }
+    }
+
+    #[test]
+    fn solves_linear_curve() {
+        let curve = line();
+
+        assert_close(&curve.solve_for_x(0.0), &[0.0]);
+        assert_close(&curve.solve_for_x(0.25), &[0.25]);
+        assert_close(&curve.solve_for_x(1.0), &[1.0]);
+        assert_eq!(curve.first_x_at(0.5), Some(0.5));
+        assert!(curve.solve_for_x(1.5).is_empty());
+    }
+
+    #[test]
+    fn solves_every_crossing() {
+        // Up to the middle and back down
+        let curve = Curve::from_points([
+            Point::from_pos(pos2(0.0, 0.0)),
+            Point::from_pos(pos2(0.5, 1.0)),
+            Point::from_pos(pos2(1.0, 0.0)),
+        ]);
+
+        assert_close(&curve.solve_for_x(0.5), &[0.25, 0.75]);
+        // The shared point is reported once
+        assert_close(&curve.solve_for_x(1.0), &[0.5]);
+        assert_eq!(curve.first_x_at(0.5), Some(0.25));
+    }
+
+    #[test]
+    fn roots_match_samples() {
+        let curve = Curve::from_points([
+            free(0.0, 0.1, 3.0),
+            free(0.6, 0.8, -2.0),
+            free(1.0, 0.3, 1.0),
+        ]);
+
+        for value in [0.2, 0.4, 0.6] {
+            let roots = curve.solve_for_x(value);
+            assert!(!roots.is_empty());
+            for x in roots {
+                assert!((curve.sample(x) - value).abs() < 0.0001);
+            }
+        }
+    }
+
+    #[test]
+    fn solves_flat_and_constant_curves() {
+        let flat = Curve::from_points([
+            Point::from_pos(pos2(0.0, 0.0)),
+            Point::from_pos(pos2(0.6, 0.5)),
+            Point::from_pos(pos2(1.0, 0.5)),
+        ]);
+        assert_close(&flat.solve_for_x(0.5), &[0.6]);
+
+        let single = Curve::from_points([Point::from_pos(pos2(0.3, 0.7))]);
+        assert_close(&single.solve_for_x(0.7), &[0.3]);
+        assert!(single.solve_for_x(0.2).is_empty());
+
+        assert!(Curve::default().solve_for_x(0.5).is_empty());
+        assert_eq!(Curve::default().first_x_at(0.5), None);
+    }
+
+    #[test]
+    fn solves_jumps() {
+        let curve = Curve::from_points([
+            Point::from_pos(pos2(0.0, 0.0)),
+            Point::from_pos(pos2(0.5, 0.0)),
+            Point::from_pos(pos2(0.5, 1.0)),
+            Point::from_pos(pos2(1.0, 1.0)),
+        ]);
+
+        assert_close(&curve.solve_for_x(0.5), &[0.5]);
+    }
+
+    #[test]
+    fn ignores_non_finite_values() {
+        let curve = line();
+
+        assert!(curve.solve_for_x(f32::NAN).is_empty());
+        assert!(curve.solve_for_x(f32::INFINITY).is_empty());
+    }
+
+    #[test]
+    fn finds_value_range_with_overshoot() {
+        assert_eq!(line().value_range(), Some(Rangef::new(0.0, 1.0)));
+
+        // Tangents push the segment above its second point
+        let curve = Curve::from_points([free(0.0, 0.2, 2.0), free(1.0, 0.6, 0.0)]);
+        let range = curve.value_range().unwrap();
+        assert_eq!(range.min, 0.2);
+        assert!(range.max > 0.6);
+
+        let peak = (0..=1000)
+            .map(|i| curve.sample(i as f32 / 1000.0))
+            .fold(0.0, f32::max);
+        assert!((range.max - peak).abs() < 0.001);
+    }
+
+    #[test]
+    fn analysis_follows_clamped_samples() {
+        // Dips below 0 and rises above 1, both clamped by `sample`
+        let curve = Curve::from_points([free(0.0, 0.0, -1.0), free(1.0, 1.0, -1.0)]);
+
+        assert!(curve.sample(0.1) >= 0.0);
+        assert_eq!(curve.value_range(), Some(Rangef::new(0.0, 1.0)));
+        assert!(curve.is_monotonic());
+        assert!(curve.overshoot_ranges().is_empty());
+        assert!(curve.solve_for_x(-0.01).is_empty());
+        assert!(curve.solve_for_x(1.01).is_empty());
+        for x in curve.solve_for_x(0.0) {
+            assert_eq!(curve.sample(x), 0.0);
+        }
+    }
+
+    #[test]
+    fn finds_bounds() {
+        let curve = Curve::from_points([
+            Point::from_pos(pos2(0.2, 0.3)),
+            Point::from_pos(pos2(0.5, 0.9)),
+            Point::from_pos(pos2(0.8, 0.1)),
+        ]);
+
+        assert_eq!(
+            curve.bounds(),
+            Some(Rect::from_min_max(pos2(0.2, 0.1), pos2(0.8, 0.9)))
+        );
+        assert_eq!(Curve::default().bounds(), None);
+        assert_eq!(Curve::default().value_range(), None);
+
+        let single = Curve::from_points([Point::from_pos(pos2(0.5, 0.4))]);
+        assert_eq!(single.value_range(), Some(Rangef::point(0.4)));
+    }
+
+    #[test]
+    fn detects_monotonicity() {
+        assert!(line().is_monotonic());
+        assert!(Curve::default().is_monotonic());
+
+        let flat_then_down = Curve::from_points([
+            Point::from_pos(pos2(0.0, 1.0)),
+            Point::from_pos(pos2(0.5, 1.0)),
+            Point::from_pos(pos2(1.0, 0.0)),
+        ]);
+        assert!(flat_then_down.is_monotonic());
+
+        let peak = Curve::from_points([
+            Point::from_pos(pos2(0.0, 0.0)),
+            Point::from_pos(pos2(0.5, 1.0)),
+            Point::from_pos(pos2(1.0, 0.0)),
+        ]);
+        assert!(!peak.is_monotonic());
+
+        // Both points rise, but the tangents make the segment turn back
+        let overshoot = Curve::from_points([free(0.0, 0.2, 3.0), free(1.0, 0.6, 3.0)]);
+        assert!(!overshoot.is_monotonic());
+    }
+
+    #[test]
+    fn finds_overshoot_ranges() {
+        assert!(line().overshoot_ranges().is_empty());
+
+        let curve = Curve::from_points([free(0.0, 0.2, 2.0), free(1.0, 0.6, 0.0)]);
+        let ranges = curve.overshoot_ranges();
+        assert_eq!(ranges.len(), 1);
+        assert!(ranges[0].max > 0.99);
+        for x in [ranges[0].min + 0.01, ranges[0].center()] {
+            assert!(curve.sample(x) > 0.6);
+        }
+    }
+
+    #[test]
+    fn monotone_constraint_removes_overshoot() {
+        let mut curve = Curve::from_points([
+            free(0.0, 0.0, 0.0),
+            free(0.5, 0.5, 4.0),
+            free(1.0, 0.6, 0.0),
+        ]);
+        assert!(!curve.overshoot_ranges().is_empty());
+
+        curve.set_monotone_constraint(true);
+        assert!(curve.monotone_constraint());
+        assert!(curve.overshoot_ranges().is_empty());
+        assert!(curve.is_monotonic());
+    }
+
+    #[test]
+    fn monotone_constraint_limits_edits() {
+        let mut curve = line();
+        curve.set_monotone_constraint(true);
+
+        // Against the secant, flattened
+        curve.set_right_tan(0, -2.0);
+        assert_eq!(curve.get_right_tan(0), Some(0.0));
+
+        // Too steep, scaled onto the Fritsch–Carlson circle
+        curve.set_left_tan(1, 10.0);
+        let (alpha, beta) = (
+            curve.get_right_tan(0).unwrap(),
+            curve.get_left_tan(1).unwrap(),
+        );
+        assert!(alpha.hypot(beta) <= 3.0 + 0.0001);
+        assert!(curve.is_monotonic());
+
+        // Moved points keep the constraint
+        curve.add_point(free(0.5, 0.9, 5.0));
+        assert!(curve.overshoot_ranges().is_empty());
+    }
+
+    #[test]
+    fn monotone_constraint_flattens_flat_segments() {
+        let mut curve = Curve::from_points([free(0.0, 0.5, 1.0), free(1.0, 0.5, 1.0)]);
+        curve.set_monotone_constraint(true);
+
+        assert_eq!(curve.get_right_tan(0), Some(0.0));
+        assert_eq!(curve.get_left_tan(1), Some(0.0));
+        assert_eq!(curve.value_range(), Some(Rangef::point(0.5)));
+    }
+
+    #[test]
+    fn monotone_constraint_handles_small_curves() {
+        for mut curve in [
+            Curve::default(),
+            Curve::from_points([Point::from_pos(pos2(0.5, 0.5))]),
+        ] {
+            curve.set_monotone_constraint(true);
+            assert!(curve.is_monotonic());
+        }
+
+        // A jump has no slope to limit
+        let mut jump = Curve::from_points([free(0.5, 0.0, 1.0), free(0.5, 1.0, 1.0)]);
+        jump.set_monotone_constraint(true);
+        assert_eq!(jump.get_right_tan(0), Some(1.0));
+    }
+
+    #[test]
+    fn reports_empty_curves() {
+        let mut curve = Curve::default();
+
+        assert_eq!(curve.try_get_index(0.5), Err(CurveError::Empty));
+        assert_eq!(curve.try_sample(0.5), Err(CurveError::Empty));
+        assert_eq!(
+            curve.try_remove_point(0).map(|_| ()),
+            Err(CurveError::IndexOutOfBounds { index: 0, len: 0 })
+        );
+
+        // The infallible variants fall back instead of panicking
+        assert_eq!(curve.get_index(0.5), 0);
+        assert_eq!(curve.sample(0.5), 0.0);
+        curve.remove_point(0);
+        curve.set_position(0, pos2(0.5, 0.5));
+        curve.set_left_tan(0, 1.0);
+    }
+
+    #[test]
+    fn reports_bad_indices() {
+        let mut curve = line();
+
+        let out_of_bounds = Err(CurveError::IndexOutOfBounds { index: 2, len: 2 });
+        assert_eq!(curve.try_set_position(2, pos2(0.5, 0.5)), out_of_bounds);
+        assert_eq!(curve.try_set_left_tan(2, 1.0), out_of_bounds);
+        assert_eq!(curve.try_set_right_tan(2, 1.0), out_of_bounds);
+        assert_eq!(curve.try_remove_point(2).map(|_| ()), out_of_bounds);
+        assert_eq!(curve.points().len(), 2);
+    }
+
+    #[test]
+    fn reports_crossed_neighbors() {
+        let mut curve = Curve::from_points([
+            Point::from_pos(pos2(0.0, 0.0)),
+            Point::from_pos(pos2(0.5, 0.5)),
+            Point::from_pos(pos2(0.7, 1.0)),
+        ]);
+
+        assert_eq!(
+            curve.try_set_position(1, pos2(0.8, 0.5)),
+            Err(CurveError::CrossesNeighbor {
+                index: 1,
+                neighbor: 2,
+                x: 0.8,
+            })
+        );
+        assert_eq!(
+            curve.try_set_position(2, pos2(0.4, 0.5)),
+            Err(CurveError::CrossesNeighbor {
+                index: 2,
+                neighbor: 1,
+                x: 0.4,
+            })
+        );
+        assert_eq!(curve.get_position(1), Some(pos2(0.5, 0.5)));
+
+        // Positions are clamped before they are checked
+        assert_eq!(curve.try_set_position(2, pos2(2.0, 0.5)), Ok(()));
+        assert_eq!(curve.get_position(2), Some(pos2(1.0, 0.5)));
+    }
+
+    #[test]
+    fn reports_non_finite_values() {
+        let mut curve = line();
+
+        assert!(matches!(
+            curve.try_set_left_tan(0, f32::NAN),
+            Err(CurveError::NonFinite(value)) if value.is_nan()
+        ));
+        assert_eq!(
+            curve.try_set_right_tan(0, f32::INFINITY),
+            Err(CurveError::NonFinite(f32::INFINITY))
+        );
+        assert_eq!(
+            curve.try_set_position(0, pos2(f32::NEG_INFINITY, 0.0)),
+            Err(CurveError::NonFinite(f32::NEG_INFINITY))
+        );
+        assert!(curve.try_sample(f32::NAN).is_err());
+        assert!(curve
+            .try_add_point(Point::from_pos(pos2(f32::NAN, 0.5)))
+            .is_err());
+        assert_eq!(curve.add_point(Point::from_pos(pos2(0.5, f32::NAN))), 0);
+
+        assert_eq!(curve.points().len(), 2);
+        assert_eq!(curve.get_right_tan(0), Some(1.0));
+    }
+
+    #[test]
+    fn formats_errors() {
+        assert_eq!(
+            CurveError::<f32>::IndexOutOfBounds { index: 3, len: 2 }.to_string(),
+            "point 3 does not exist, the curve has 2 points"
+        );
+        assert_eq!(
+            CurveError::CrossesNeighbor {
+                index: 1,
+                neighbor: 0,
+                x: 0.25f32,
+            }
+            .to_string(),
+            "moving point 1 to x = 0.25 would cross point 0"
+        );
+        assert_eq!(
+            CurveError::<f32>::Empty.to_string(),
+            "the curve has no points"
+        );
+    }
 }
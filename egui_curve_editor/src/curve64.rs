@@ -0,0 +1,120 @@
+// A `Curve` in `f64`, for callers sampling at offsets where `f32` runs out of precision.
+//
+// Offsets are still clamped to `0..=1` like a `Curve`'s, so a long timeline normalizes its time in
+// `f64` first, e.g. `elapsed / duration`, and gets the precision from there.
+//
+// It is the same model as `Curve` and serializes to the same shape, so either can read the other's
+// data. There is no editor for it, edit a `Curve` converted from it and convert back.
+
+use crate::{Curve, CurveT, PointT};
+
+/// A point of a [`Curve64`].
+pub type Point64 = PointT<f64>;
+
+/// Like [`Curve`], with `f64` positions, tangents and offsets.
+///
+/// Offsets are clamped to `0..=1` all the same, callers normalize larger ranges before sampling.
+pub type Curve64 = CurveT<f64>;
+
+#[allow(unused)]
+impl Curve64 {
+    /// Rounds to a [`Curve`], for editing or for the parts of the crate that only take one.
+    pub fn to_curve(&self) -> Curve {
+        self.cast()
+    }
+}
+
+impl From<&Curve> for Curve64 {
+    fn from(curve: &Curve) -> Self {
+        curve.cast()
+    }
+}
+
+impl From<&Curve64> for Curve {
+    fn from(curve: &Curve64) -> Self {
+        curve.cast()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::{dpos2, pos2};
+    use crate::{CurveError, Point, TangentMode};
+
+    #[test]
+    fn samples_like_curve() {
+        let curve = Curve::from_points([
+            Point::from_pos(pos2(0.0, 0.2)),
+            Point::new(
+                pos2(0.4, 0.9),
+                0.5,
+                -1.0,
+                TangentMode::Free,
+                TangentMode::Free,
+            ),
+            Point::from_pos(pos2(1.0, 0.1)),
+        ]);
+        let curve64 = Curve64::from(&curve);
+
+        for i in 0..=20 {
+            let x = i as f32 / 20.0;
+            assert!((curve.sample(x) as f64 - curve64.sample(x as f64)).abs() < 1e-6);
+        }
+        assert_eq!(
+            curve64.value_range().is_some(),
+            curve.value_range().is_some()
+        );
+        assert_eq!(curve64.is_monotonic(), curve.is_monotonic());
+    }
+
+    #[test]
+    fn keeps_f64_precision() {
+        let mut curve = Curve64::default();
+        curve.add_point(Point64::from_pos(dpos2(0.0, 0.0)));
+        curve.add_point(Point64::from_pos(dpos2(1.0, 1.0)));
+
+        let x = 0.123_456_789_012_345;
+        assert!((curve.sample(x) - x).abs() < 1e-12);
+        assert_eq!(curve.solve_for_x(0.5), vec![0.5]);
+
+        // Offsets outside `0..=1` clamp, normalizing keeps the precision
+        assert_eq!(curve.sample(86_400.5), 1.0);
+        let x = 86_400.5 / 172_800.0;
+        assert!((curve.sample(x) - x).abs() < 1e-12);
+    }
+
+    #[test]
+    fn reports_errors_in_f64() {
+        let mut curve = Curve64::linear();
+        let x = 0.000_000_000_1;
+
+        assert_eq!(curve.try_set_position(1, dpos2(x, 0.5)), Ok(()));
+        assert_eq!(
+            curve.try_set_position(0, dpos2(0.1, 0.5)),
+            Err(CurveError::CrossesNeighbor {
+                index: 0,
+                neighbor: 1,
+                x: 0.1,
+            })
+        );
+        assert_eq!(
+            curve
+                .try_sample(f64::NAN)
+                .map_err(|error| matches!(error, CurveError::NonFinite(_))),
+            Err(true)
+        );
+        assert_eq!(curve.get_position(1), Some(dpos2(x, 0.5)));
+    }
+
+    #[test]
+    fn transforms_and_simplifies() {
+        let mut curve = Curve64::from_points(
+            (0..=10).map(|i| Point64::from_pos(dpos2(i as f64 / 10.0, i as f64 / 10.0))),
+        );
+
+        assert_eq!(curve.simplify(1e-9), 9);
+        curve.reverse();
+        assert!((curve.sample(0.25) - 0.75).abs() < 1e-12);
+    }
+}
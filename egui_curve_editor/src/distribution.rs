@@ -0,0 +1,174 @@
+use crate::prelude::*;
+use crate::Curve;
+
+/// Uses a [`Curve`] as a probability density over `0..=1`.
+///
+/// The curve is integrated into a normalized cumulative table once, so that uniform numbers can be
+/// mapped to values through the inverse of the cumulative distribution.
+#[derive(Clone)]
+pub struct CurveDistribution {
+    cdf: Vec<f32>,
+}
+
+#[allow(unused)]
+impl CurveDistribution {
+    pub const DEFAULT_RESOLUTION: usize = 1024;
+
+    /// Returns `None` if the curve has no area to sample from.
+    pub fn new(curve: &Curve) -> Option<Self> {
+        Self::with_resolution(curve, Self::DEFAULT_RESOLUTION)
+    }
+
+    pub fn with_resolution(curve: &Curve, resolution: usize) -> Option<Self> {
+        let resolution = resolution.max(1);
+        let step = 1.0 / resolution as f32;
+
+        // Trapezoidal integration
+        let mut cdf = Vec::with_capacity(resolution + 1);
+        let mut total = 0.0;
+        let mut previous = curve.sample(0.0).max(0.0);
+        cdf.push(0.0);
+        for i in 1..=resolution {
+            let density = curve.sample(i as f32 * step).max(0.0);
+            total += (previous + density) * 0.5 * step;
+            previous = density;
+            cdf.push(total);
+        }
+
+        const EPSILON: f32 = 0.00001;
+        if total < EPSILON {
+            return None;
+        }
+
+        for value in cdf.iter_mut() {
+            *value /= total;
+        }
+
+        Some(Self { cdf })
+    }
+
+    /// Maps a uniform number in `0..=1` to a value distributed like the curve, NaN counts as `0`.
+    pub fn inverse_cdf(&self, uniform: f32) -> f32 {
+        let uniform = clamp_unit(uniform);
+        let resolution = self.cdf.len() - 1;
+
+        // First entry reaching the uniform number, the one before is below it
+        let i = self
+            .cdf
+            .partition_point(|&value| value < uniform)
+            .clamp(1, resolution);
+
+        let (low, high) = (self.cdf[i - 1], self.cdf[i]);
+        let t = if high > low {
+            (uniform - low) / (high - low)
+        } else {
+            0.0
+        };
+
+        (i as f32 - 1.0 + t) / resolution as f32
+    }
+
+    /// The probability of a value at or below `value`, NaN counts as `0`.
+    pub fn cdf(&self, value: f32) -> f32 {
+        let resolution = self.cdf.len() - 1;
+        let scaled = clamp_unit(value) * resolution as f32;
+
+        let i = (scaled as usize).min(resolution - 1);
+        let t = scaled - i as f32;
+
+        self.cdf[i] + (self.cdf[i + 1] - self.cdf[i]) * t
+    }
+}
+
+fn clamp_unit(value: f32) -> f32 {
+    if value.is_nan() {
+        return 0.0;
+    }
+
+    value.clamp(0.0, 1.0)
+}
+
+#[cfg(feature = "rand")]
+impl rand::distributions::Distribution<f32> for CurveDistribution {
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> f32 {
+        self.inverse_cdf(rng.gen())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::pos2;
+    use crate::Point;
+
+    fn curve(points: &[(f32, f32)]) -> Curve {
+        Curve::from_points(points.iter().map(|&(x, y)| Point::from_pos(pos2(x, y))))
+    }
+
+    #[test]
+    fn flat_curve_is_uniform() {
+        let distribution = CurveDistribution::new(&curve(&[(0.0, 0.5), (1.0, 0.5)])).unwrap();
+
+        for u in [0.0, 0.1, 0.5, 0.9, 1.0] {
+            assert!((distribution.inverse_cdf(u) - u).abs() < 0.001);
+            assert!((distribution.cdf(u) - u).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn ramp_follows_its_integral() {
+        // A density of `2x` has a cumulative distribution of `x^2`
+        let distribution = CurveDistribution::new(&curve(&[(0.0, 0.0), (1.0, 1.0)])).unwrap();
+
+        assert!((distribution.cdf(0.5) - 0.25).abs() < 0.001);
+        assert!((distribution.inverse_cdf(0.25) - 0.5).abs() < 0.001);
+        assert!((distribution.inverse_cdf(distribution.cdf(0.8)) - 0.8).abs() < 0.001);
+    }
+
+    #[test]
+    fn skips_zero_density() {
+        let distribution =
+            CurveDistribution::new(&curve(&[(0.0, 0.0), (0.5, 0.0), (0.5, 1.0), (1.0, 1.0)]))
+                .unwrap();
+
+        assert!(distribution.inverse_cdf(0.0001) >= 0.49);
+        assert_eq!(distribution.inverse_cdf(1.0), 1.0);
+    }
+
+    #[test]
+    fn rejects_curves_without_area() {
+        assert!(CurveDistribution::new(&Curve::default()).is_none());
+        assert!(CurveDistribution::new(&curve(&[(0.0, 0.0), (1.0, 0.0)])).is_none());
+    }
+
+    #[test]
+    fn single_point_is_constant_density() {
+        let distribution = CurveDistribution::new(&curve(&[(0.3, 0.8)])).unwrap();
+
+        assert!((distribution.inverse_cdf(0.5) - 0.5).abs() < 0.001);
+    }
+
+    #[test]
+    fn clamps_out_of_range_and_non_finite_input() {
+        let distribution = CurveDistribution::with_resolution(&curve(&[(0.0, 1.0)]), 0).unwrap();
+
+        assert_eq!(distribution.inverse_cdf(-1.0), 0.0);
+        assert_eq!(distribution.inverse_cdf(2.0), 1.0);
+        assert_eq!(distribution.inverse_cdf(f32::NAN), 0.0);
+        assert_eq!(distribution.cdf(f32::NAN), 0.0);
+        assert_eq!(distribution.cdf(f32::INFINITY), 1.0);
+    }
+
+    #[cfg(feature = "rand")]
+    #[test]
+    fn samples_with_rand() {
+        use rand::distributions::Distribution;
+
+        let distribution = CurveDistribution::new(&curve(&[(0.0, 0.0), (1.0, 1.0)])).unwrap();
+        let mut rng = rand::rngs::mock::StepRng::new(0, u64::MAX / 100);
+
+        for value in distribution.sample_iter(&mut rng).take(100) {
+            assert!((0.0..=1.0).contains(&value));
+        }
+    }
+}
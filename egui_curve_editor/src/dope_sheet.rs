@@ -0,0 +1,322 @@
+use egui::NumExt;
+
+use crate::math::pos2;
+use crate::{CurveEditor, Timeline, Track};
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Default)]
+struct DopeSheetState {
+    dragging: bool,
+    /// Track and key index of every selected key.
+    selection: Vec<(usize, usize)>,
+    /// Drag not applied yet, less than a frame when snapping.
+    remainder: f32,
+    open: Option<usize>,
+}
+
+/// Shows the keys of many [`Track`]s over time, one row each.
+///
+/// Clicking a key selects it, shift toggles it in the selection, and dragging moves every selected
+/// key in time. Clicking the name of a track opens it in a [`CurveEditor`] under the sheet.
+pub struct DopeSheet<'a> {
+    tracks: &'a mut [Track],
+    width: Option<f32>,
+    name_width: f32,
+    row_height: f32,
+    timeline: Option<Timeline>,
+}
+
+#[allow(unused)]
+impl<'a> DopeSheet<'a> {
+    const MARKER_SIZE: f32 = 10.0;
+    const HEADER_HEIGHT: f32 = 14.0;
+
+    pub fn new(tracks: &'a mut [Track]) -> Self {
+        Self {
+            tracks,
+            width: None,
+            name_width: 100.0,
+            row_height: 20.0,
+            timeline: None,
+        }
+    }
+
+    pub fn with_width(self, width: f32) -> Self {
+        Self {
+            width: Some(width),
+            ..self
+        }
+    }
+
+    pub fn with_name_width(self, name_width: f32) -> Self {
+        Self { name_width, ..self }
+    }
+
+    pub fn with_row_height(self, row_height: f32) -> Self {
+        Self { row_height, ..self }
+    }
+
+    /// Labels the frames of `timeline` above the rows and snaps moved keys to them.
+    pub fn with_timeline(self, timeline: Timeline) -> Self {
+        Self {
+            timeline: Some(timeline),
+            ..self
+        }
+    }
+
+    fn load_state(ctx: &egui::Context, id: egui::Id) -> Option<DopeSheetState> {
+        ctx.data_mut(|d| d.get_persisted(id))
+    }
+
+    fn store_state(ctx: &egui::Context, id: egui::Id, state: DopeSheetState) {
+        ctx.data_mut(|d| d.insert_persisted(id, state));
+    }
+
+    /// Track, key and position of every key.
+    fn markers(&self, keys_rect: egui::Rect) -> Vec<(usize, usize, egui::Pos2)> {
+        self.tracks
+            .iter()
+            .enumerate()
+            .flat_map(|(track, Track { curve, .. })| {
+                let y = keys_rect.top() + (track as f32 + 0.5) * self.row_height;
+
+                curve.points().iter().enumerate().map(move |(key, point)| {
+                    let x = keys_rect.left() + point.pos().x * keys_rect.width();
+                    (track, key, egui::pos2(x, y))
+                })
+            })
+            .collect()
+    }
+
+    /// Moves the selected keys by `delta`, in whole frames if there is a timeline.
+    ///
+    /// Moved keys then land on the nearest frame, unless a neighbor is in the way.
+    fn retime(&mut self, state: &mut DopeSheetState, delta: f32) {
+        let mut delta = state.remainder + delta;
+        if let Some(timeline) = self.timeline {
+            let frame = timeline.offset_at_frame(1);
+            state.remainder = delta - (delta / frame).round() * frame;
+            delta -= state.remainder;
+        }
+        if delta == 0.0 {
+            return;
+        }
+
+        let mut selection = vec![];
+        for (track, Track { curve, .. }) in self.tracks.iter_mut().enumerate() {
+            let keys: Vec<usize> = state
+                .selection
+                .iter()
+                .filter(|(selected, _)| *selected == track)
+                .map(|&(_, key)| key)
+                .collect();
+            if keys.is_empty() {
+                continue;
+            }
+
+            let moved = curve.retime_points(&keys, delta);
+            if let Some(timeline) = self.timeline {
+                for &key in &moved {
+                    if let Some(pos) = curve.get_position(key) {
+                        let _ = curve.try_set_position(key, pos2(timeline.snap(pos.x), pos.y));
+                    }
+                }
+            }
+            selection.extend(moved.into_iter().map(|key| (track, key)));
+        }
+        state.selection = selection;
+    }
+
+    fn paint_marker(
+        painter: &egui::Painter,
+        pos: egui::Pos2,
+        fill: egui::Color32,
+        stroke: egui::Stroke,
+    ) {
+        let half = Self::MARKER_SIZE * 0.5;
+
+        painter.add(egui::Shape::convex_polygon(
+            vec![
+                pos + egui::vec2(0.0, -half),
+                pos + egui::vec2(half, 0.0),
+                pos + egui::vec2(0.0, half),
+                pos + egui::vec2(-half, 0.0),
+            ],
+            fill,
+            stroke,
+        ));
+    }
+}
+
+impl egui::Widget for DopeSheet<'_> {
+    fn ui(mut self, ui: &mut egui::Ui) -> egui::Response {
+        let width = self
+            .width
+            .unwrap_or_else(|| ui.available_size_before_wrap().x)
+            .at_least(self.name_width + Self::MARKER_SIZE * 2.0);
+        let header = if self.timeline.is_some() {
+            Self::HEADER_HEIGHT
+        } else {
+            0.0
+        };
+        let size = egui::vec2(width, header + self.tracks.len() as f32 * self.row_height);
+
+        let id = ui.next_auto_id();
+        let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click_and_drag());
+        ui.ctx().check_for_id_clash(id, rect, "DopeSheet");
+
+        // Markers overhang the ends of the rows by half their width
+        let keys_rect = egui::Rect::from_min_max(
+            egui::pos2(
+                rect.left() + self.name_width + Self::MARKER_SIZE * 0.5,
+                rect.top() + header,
+            ),
+            rect.max - egui::vec2(Self::MARKER_SIZE * 0.5, 0.0),
+        );
+
+        let mut state = Self::load_state(ui.ctx(), id).unwrap_or_default();
+
+        // Drop a selection the tracks no longer have
+        let tracks = &*self.tracks;
+        state.selection.retain(|&(track, key)| {
+            tracks
+                .get(track)
+                .is_some_and(|track| key < track.curve.points().len())
+        });
+        state.open = state.open.filter(|&open| open < tracks.len());
+
+        if (response.clicked() || response.dragged())
+            && response.hover_pos().is_some()
+            && !state.dragging
+        {
+            let pos = response.hover_pos().unwrap();
+            let shift = ui.input(|i| i.modifiers.shift);
+
+            let near = self
+                .markers(keys_rect)
+                .into_iter()
+                .filter(|(_, _, marker)| marker.distance(pos) < Self::MARKER_SIZE)
+                .min_by(|a, b| a.2.distance(pos).total_cmp(&b.2.distance(pos)));
+
+            if let Some((track, key, _)) = near {
+                let selected = state.selection.contains(&(track, key));
+
+                // Select key, shift toggles it in the selection
+                if response.clicked_by(egui::PointerButton::Primary) {
+                    if !shift {
+                        state.selection.clear();
+                    }
+                    if selected && shift {
+                        state.selection.retain(|&entry| entry != (track, key));
+                    } else {
+                        state.selection.push((track, key));
+                    }
+                }
+
+                // Start dragging, keeping the selection if the key is part of it
+                if response.dragged_by(egui::PointerButton::Primary) {
+                    if !selected {
+                        if !shift {
+                            state.selection.clear();
+                        }
+                        state.selection.push((track, key));
+                    }
+                    state.dragging = true;
+                    state.remainder = 0.0;
+                }
+            } else if response.clicked_by(egui::PointerButton::Primary) {
+                let row = ((pos.y - keys_rect.top()) / self.row_height).floor();
+                if pos.x < rect.left() + self.name_width && row >= 0.0 {
+                    // Open or close the track
+                    let row = row as usize;
+                    state.open = (state.open != Some(row)).then_some(row);
+                } else if !shift {
+                    state.selection.clear();
+                }
+            }
+        }
+
+        // Stop dragging?
+        if state.dragging && (response.drag_stopped() || !response.is_pointer_button_down_on()) {
+            state.dragging = false;
+        }
+
+        // Handle dragging
+        if state.dragging {
+            let delta = response.drag_delta().x / keys_rect.width();
+            self.retime(&mut state, delta);
+        }
+
+        let painter = ui.painter().with_clip_rect(rect.expand(1.0));
+        let visuals = ui.style().interact(&response);
+
+        // Rows
+        for (index, track) in self.tracks.iter().enumerate() {
+            let row_rect = egui::Rect::from_min_size(
+                egui::pos2(
+                    rect.left(),
+                    keys_rect.top() + index as f32 * self.row_height,
+                ),
+                egui::vec2(rect.width(), self.row_height),
+            );
+            let fill = if state.open == Some(index) {
+                ui.visuals().selection.bg_fill.gamma_multiply(0.3)
+            } else if index % 2 == 0 {
+                ui.visuals().faint_bg_color
+            } else {
+                ui.visuals().extreme_bg_color
+            };
+            painter.rect_filled(row_rect, 0, fill);
+
+            painter.with_clip_rect(row_rect).text(
+                row_rect.left_center() + egui::vec2(4.0, 0.0),
+                egui::Align2::LEFT_CENTER,
+                &track.name,
+                egui::FontId::proportional(12.0),
+                ui.visuals().text_color(),
+            );
+        }
+
+        // Frames
+        if let Some(timeline) = self.timeline {
+            let frames_rect =
+                egui::Rect::from_min_max(egui::pos2(keys_rect.left(), rect.top()), keys_rect.max);
+            CurveEditor::paint_frames(ui, frames_rect, timeline);
+        }
+
+        painter.vline(
+            rect.left() + self.name_width,
+            rect.y_range(),
+            ui.visuals().widgets.noninteractive.bg_stroke,
+        );
+
+        for (track, key, pos) in self.markers(keys_rect) {
+            let fill = if state.selection.contains(&(track, key)) {
+                ui.visuals().selection.bg_fill
+            } else {
+                visuals.bg_fill
+            };
+            Self::paint_marker(&painter, pos, fill, visuals.fg_stroke);
+        }
+
+        let open = state.open;
+        Self::store_state(ui.ctx(), id, state);
+
+        // Open track
+        if let Some((index, track)) = open.and_then(|open| Some((open, self.tracks.get_mut(open)?)))
+        {
+            ui.label(&track.name);
+
+            // Every track keeps its own editor state
+            let timeline = self.timeline;
+            ui.push_id(index, |ui| {
+                let mut editor = CurveEditor::new(&mut track.curve);
+                if let Some(timeline) = timeline {
+                    editor = editor.with_timeline(timeline);
+                }
+                ui.add(editor);
+            });
+        }
+
+        response
+    }
+}
@@ -0,0 +1,894 @@
+use egui::NumExt;
+
+use crate::math::{pos2, vec2, Pos2, Rangef, Rect, Vec2};
+use crate::{CsvImport, Curve, Point, Timeline};
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, Default, PartialEq)]
+pub(crate) enum DragTarget {
+    LeftTangent,
+    #[default]
+    Handle,
+    RightTangent,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Default)]
+struct CurveEditorState {
+    dragging: Option<DragTarget>,
+    selected: Option<usize>,
+    #[serde(default)]
+    selection: Vec<usize>,
+    #[serde(default)]
+    message: Option<String>,
+    #[serde(default)]
+    stroke: Vec<Pos2>,
+    #[serde(default)]
+    transform: TransformInput,
+}
+
+/// Values typed into the transform commands of the context menu.
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy)]
+struct TransformInput {
+    scale: Vec2,
+    offset: Vec2,
+}
+
+impl Default for TransformInput {
+    fn default() -> Self {
+        Self {
+            scale: Vec2::splat(1.0),
+            offset: Vec2::ZERO,
+        }
+    }
+}
+
+impl CurveEditorState {
+    pub fn load(ctx: &egui::Context, id: egui::Id) -> Option<Self> {
+        ctx.data_mut(|d| d.get_persisted(id))
+    }
+
+    pub fn store(self, ctx: &egui::Context, id: egui::Id) {
+        ctx.data_mut(|d| d.insert_persisted(id, self));
+    }
+}
+
+pub struct CurveEditor<'a> {
+    curve: &'a mut Curve,
+    min_size: egui::Vec2,
+    max_size: Option<egui::Vec2>,
+    width: Option<f32>,
+    height: Option<f32>,
+    view_aspect: f32,
+    show_overshoot: bool,
+    paste_fit: Option<f32>,
+    simplify_tolerance: f32,
+    draw_mode: bool,
+    draw_smoothness: f32,
+    timeline: Option<Timeline>,
+    playhead: Option<f32>,
+}
+
+#[allow(unused)]
+impl<'a> CurveEditor<'a> {
+    pub fn new(curve: &'a mut Curve) -> Self {
+        Self {
+            curve,
+            min_size: egui::vec2(40.0, 40.0),
+            max_size: None,
+            width: None,
+            height: None,
+            view_aspect: 13.0 / 6.0,
+            show_overshoot: false,
+            paste_fit: None,
+            simplify_tolerance: 0.005,
+            draw_mode: false,
+            draw_smoothness: 0.01,
+            timeline: None,
+            playhead: None,
+        }
+    }
+
+    pub fn with_min_size(self, min_size: egui::Vec2) -> Self {
+        Self { min_size, ..self }
+    }
+
+    pub fn with_max_size(self, max_size: egui::Vec2) -> Self {
+        Self {
+            max_size: Some(max_size),
+            ..self
+        }
+    }
+
+    pub fn with_width(self, width: f32) -> Self {
+        Self {
+            width: Some(width),
+            ..self
+        }
+    }
+
+    pub fn with_height(self, height: f32) -> Self {
+        Self {
+            height: Some(height),
+            ..self
+        }
+    }
+
+    pub fn with_size(self, size: egui::Vec2) -> Self {
+        Self {
+            width: Some(size.x),
+            height: Some(size.y),
+            ..self
+        }
+    }
+
+    pub fn with_aspect(self, view_aspect: f32) -> Self {
+        Self {
+            view_aspect,
+            ..self
+        }
+    }
+
+    /// Highlights where segments overshoot the values of their points.
+    pub fn with_overshoot_highlight(self, show_overshoot: bool) -> Self {
+        Self {
+            show_overshoot,
+            ..self
+        }
+    }
+
+    /// Replaces the curve with a fit of `x, y` rows pasted while hovering the editor.
+    ///
+    /// Rows may be separated by commas, semicolons or tabs, their ranges are mapped to `0..=1`.
+    pub fn with_paste_fit(self, tolerance: f32) -> Self {
+        Self {
+            paste_fit: Some(tolerance),
+            ..self
+        }
+    }
+
+    /// Error allowed by the simplify commands of the context menu.
+    pub fn with_simplify_tolerance(self, simplify_tolerance: f32) -> Self {
+        Self {
+            simplify_tolerance,
+            ..self
+        }
+    }
+
+    /// Dragging sketches a stroke instead of editing points, see [`Curve::splice_fit`].
+    pub fn with_draw_mode(self, draw_mode: bool) -> Self {
+        Self { draw_mode, ..self }
+    }
+
+    /// Error allowed when fitting a sketched stroke, larger values give smoother curves.
+    pub fn with_draw_smoothness(self, draw_smoothness: f32) -> Self {
+        Self {
+            draw_smoothness,
+            ..self
+        }
+    }
+
+    /// Labels the x axis with the frames of `timeline` and snaps keys to them.
+    pub fn with_timeline(self, timeline: Timeline) -> Self {
+        Self {
+            timeline: Some(timeline),
+            ..self
+        }
+    }
+
+    /// Marks an offset on the curve, like the progress of a [`CurvePlayer`](crate::CurvePlayer).
+    pub fn with_playhead(self, offset: f32) -> Self {
+        Self {
+            playhead: Some(offset),
+            ..self
+        }
+    }
+
+    fn load_state(ctx: &egui::Context, id: egui::Id) -> Option<CurveEditorState> {
+        CurveEditorState::load(ctx, id)
+    }
+
+    fn store_state(ctx: &egui::Context, id: egui::Id, state: CurveEditorState) {
+        state.store(ctx, id);
+    }
+
+    fn parse_pasted_samples(text: &str) -> Option<Vec<Pos2>> {
+        let delimiter = ['\t', ';', ',']
+            .into_iter()
+            .find(|&delimiter| text.contains(delimiter))?;
+
+        // Skip a header if the first cell is not a number
+        let header = text
+            .split(delimiter)
+            .next()
+            .is_some_and(|cell| cell.trim().parse::<f32>().is_err());
+
+        let import = CsvImport::new()
+            .with_x(0)
+            .with_y(1)
+            .with_left_tan(None)
+            .with_right_tan(None)
+            .with_left_mode(None)
+            .with_right_mode(None)
+            .with_delimiter(delimiter)
+            .with_header(header)
+            .with_fit_ranges(true);
+
+        import
+            .parse(text)
+            .ok()
+            .map(|csv| csv.curve.point_positions())
+    }
+
+    /// Transform commands, applying to the selected points or else to the whole curve.
+    ///
+    /// Returns the new selection once a command ran.
+    fn transform_menu(
+        ui: &mut egui::Ui,
+        curve: &mut Curve,
+        selection: &[usize],
+        input: &mut TransformInput,
+    ) -> Option<Vec<usize>> {
+        let indices: Vec<usize> = if selection.is_empty() {
+            (0..curve.points().len()).collect()
+        } else {
+            selection.to_vec()
+        };
+
+        // Selections transform around their own bounds
+        let bounds = Rect::from_points(
+            &indices
+                .iter()
+                .filter_map(|&index| curve.get_position(index))
+                .collect::<Vec<_>>(),
+        );
+        let pivot = if selection.is_empty() {
+            pos2(0.5, 0.5)
+        } else {
+            bounds.center()
+        };
+
+        ui.label(if selection.is_empty() {
+            "Curve"
+        } else {
+            "Selection"
+        });
+
+        let mut transformed = None;
+        if ui.button("Flip vertical").clicked() {
+            transformed =
+                Some(curve.transform_points(&indices, vec2(1.0, -1.0), pivot, Vec2::ZERO));
+        }
+        if ui.button("Reverse").clicked() {
+            transformed =
+                Some(curve.transform_points(&indices, vec2(-1.0, 1.0), pivot, Vec2::ZERO));
+        }
+        if ui.button("Normalize").clicked() {
+            let target = Rangef::new(0.0, 1.0);
+            transformed = Some(if selection.is_empty() {
+                curve.normalize(target);
+                indices.clone()
+            } else {
+                curve.normalize_points(&indices, target)
+            });
+        }
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::DragValue::new(&mut input.scale.x)
+                    .speed(0.01)
+                    .prefix("x: "),
+            );
+            ui.add(
+                egui::DragValue::new(&mut input.scale.y)
+                    .speed(0.01)
+                    .prefix("y: "),
+            );
+            if ui.button("Scale").clicked() {
+                transformed =
+                    Some(curve.transform_points(&indices, input.scale, pivot, Vec2::ZERO));
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::DragValue::new(&mut input.offset.x)
+                    .speed(0.01)
+                    .prefix("x: "),
+            );
+            ui.add(
+                egui::DragValue::new(&mut input.offset.y)
+                    .speed(0.01)
+                    .prefix("y: "),
+            );
+            if ui.button("Offset").clicked() {
+                transformed =
+                    Some(curve.transform_points(&indices, Vec2::splat(1.0), pivot, input.offset));
+            }
+        });
+
+        if transformed.is_some() {
+            ui.close_menu();
+        }
+
+        // The whole curve stays unselected
+        transformed.map(|transformed| {
+            if selection.is_empty() {
+                vec![]
+            } else {
+                transformed
+            }
+        })
+    }
+
+    fn normalized_to_plot_coords(plot_rect: egui::Rect, coords: Pos2) -> egui::Pos2 {
+        plot_rect.lerp_inside(egui::vec2(coords.x, 1.0 - coords.y))
+    }
+
+    fn plot_to_normalized_coords(plot_rect: egui::Rect, coords: egui::Pos2) -> Pos2 {
+        pos2(
+            (coords.x - plot_rect.left()) / plot_rect.width(),
+            1.0 - ((coords.y - plot_rect.top()) / plot_rect.height()),
+        )
+    }
+
+    /// Ticks and labels along the top, spaced so that labels do not overlap.
+    pub(crate) fn paint_frames(ui: &egui::Ui, plot_rect: egui::Rect, timeline: Timeline) {
+        const MIN_SPACING: f32 = 48.0;
+
+        let last_frame = timeline.last_frame();
+        let frame_width = plot_rect.width() * timeline.offset_at_frame(1);
+        let step = [1, 2, 5]
+            .into_iter()
+            .flat_map(|step| (0..6).map(move |power| step * 10_usize.pow(power)))
+            .filter(|step| *step as f32 * frame_width >= MIN_SPACING)
+            .min()
+            .unwrap_or(last_frame.max(1));
+
+        let painter = ui.painter().with_clip_rect(plot_rect);
+        let stroke = ui.visuals().widgets.noninteractive.bg_stroke;
+        for frame in (0..=last_frame).step_by(step) {
+            let x = CurveEditor::normalized_to_plot_coords(
+                plot_rect,
+                pos2(timeline.offset_at_frame(frame), 1.0),
+            )
+            .x;
+
+            painter.vline(x, plot_rect.y_range(), stroke);
+            painter.text(
+                egui::pos2(x + 2.0, plot_rect.top() + 2.0),
+                egui::Align2::LEFT_TOP,
+                timeline.label(frame),
+                egui::FontId::monospace(10.0),
+                ui.visuals().weak_text_color(),
+            );
+        }
+    }
+
+    /// Index, position and tangents of the selected point, if the curve still has it.
+    fn selected_point(&self, selected: Option<usize>) -> Option<(usize, Pos2, f32, f32)> {
+        let selected = selected?;
+
+        Some((
+            selected,
+            self.curve.get_position(selected)?,
+            self.curve.get_left_tan(selected)?,
+            self.curve.get_right_tan(selected)?,
+        ))
+    }
+
+    fn get_tangents_plot_coords(
+        plot_rect: egui::Rect,
+        pos: Pos2,
+        left: f32,
+        right: f32,
+    ) -> (egui::Pos2, egui::Pos2) {
+        let left_dir = -egui::vec2(1.0, -left).normalized();
+        let right_dir = egui::vec2(1.0, -right).normalized();
+
+        let plot_pos = CurveEditor::normalized_to_plot_coords(plot_rect, pos);
+
+        let plot_left = plot_pos + left_dir * 20.0;
+        let plot_right = plot_pos + right_dir * 20.0;
+
+        (plot_left, plot_right)
+    }
+}
+
+impl<'a> egui::Widget for CurveEditor<'a> {
+    // TODO: Make textual interface
+    // TODO: Make sure tangents are always inside visible area?
+    fn ui(self, ui: &mut egui::Ui) -> egui::Response {
+        // Determine position of widget.
+        let pos = ui.available_rect_before_wrap().min;
+        // Minimum values for screen protection
+        let mut min_size = self.min_size;
+        min_size.x = min_size.x.at_least(1.0);
+        min_size.y = min_size.y.at_least(1.0);
+
+        // Determine size of widget.
+        let width = self.width;
+        let height = self.height;
+        let view_aspect = self.view_aspect;
+        let size = {
+            let mut width = width
+                .unwrap_or_else(|| {
+                    if let (Some(height), aspect) = (height, view_aspect) {
+                        height * aspect
+                    } else {
+                        ui.available_size_before_wrap().x
+                    }
+                })
+                .at_least(min_size.x);
+
+            let mut height = height
+                .unwrap_or_else(|| width / view_aspect)
+                .at_least(min_size.y);
+
+            if let Some(max_size) = self.max_size {
+                width = width.min(max_size.x);
+                height = height.min(max_size.y);
+            }
+
+            egui::vec2(width, height)
+        };
+
+        // Determine complete rect of widget.
+        let complete_rect = egui::Rect {
+            min: pos,
+            max: pos + size,
+        };
+
+        let id = ui.next_auto_id();
+
+        let plot_rect = complete_rect;
+        let sense = egui::Sense::click_and_drag();
+        let response = ui.allocate_rect(plot_rect, sense);
+
+        // Load or initialize the memory.
+        ui.ctx().check_for_id_clash(id, plot_rect, "Plot");
+
+        let show_background = true;
+        // Background
+        if show_background {
+            ui.painter()
+                .with_clip_rect(plot_rect)
+                .add(egui::epaint::RectShape::new(
+                    plot_rect,
+                    2,
+                    ui.visuals().extreme_bg_color,
+                    ui.visuals().widgets.noninteractive.bg_stroke,
+                    egui::StrokeKind::Inside,
+                ));
+        }
+
+        let mut state = CurveEditor::load_state(ui.ctx(), id).unwrap_or(CurveEditorState {
+            dragging: None,
+            selected: None,
+            selection: vec![],
+            message: None,
+            stroke: vec![],
+            transform: TransformInput::default(),
+        });
+
+        // Drop a selection the curve no longer has
+        let len = self.curve.points().len();
+        state.selected = state.selected.filter(|&selected| selected < len);
+        state.selection.retain(|&index| index < len);
+
+        if response.clicked() || response.drag_started() {
+            state.message = None;
+        }
+
+        // Fit pasted data?
+        if let Some(tolerance) = self.paste_fit.filter(|_| response.hovered()) {
+            let pasted = ui.input(|i| {
+                i.events.iter().find_map(|event| match event {
+                    egui::Event::Paste(text) => Some(text.clone()),
+                    _ => None,
+                })
+            });
+
+            if let Some(samples) = pasted.and_then(|text| CurveEditor::parse_pasted_samples(&text))
+            {
+                let monotone = self.curve.monotone_constraint();
+                *self.curve = Curve::fit(&samples, tolerance);
+                self.curve.set_monotone_constraint(monotone);
+                state.dragging = None;
+                state.selected = None;
+                state.selection.clear();
+            }
+        }
+
+        // Sketch a stroke?
+        if self.draw_mode {
+            if response.drag_started_by(egui::PointerButton::Primary) {
+                state.stroke.clear();
+
+                // The drag only starts once the pointer moved, where it was pressed comes first
+                if let Some(origin) = ui.input(|i| i.pointer.press_origin()) {
+                    state.stroke.push(
+                        CurveEditor::plot_to_normalized_coords(plot_rect, origin)
+                            .clamp(pos2(0.0, 0.0), pos2(1.0, 1.0)),
+                    );
+                }
+            }
+
+            if let Some(pos) = response
+                .interact_pointer_pos()
+                .filter(|_| response.dragged_by(egui::PointerButton::Primary))
+            {
+                let pos = CurveEditor::plot_to_normalized_coords(plot_rect, pos)
+                    .clamp(pos2(0.0, 0.0), pos2(1.0, 1.0));
+
+                // Going back over the stroke redraws that part of it
+                if let Some(last) = state.stroke.last().copied() {
+                    let retraced = Rangef::new(last.x.min(pos.x), last.x.max(pos.x));
+                    state
+                        .stroke
+                        .retain(|sample| !(retraced.min < sample.x && sample.x < retraced.max));
+                }
+                state.stroke.push(pos);
+            }
+
+            if response.drag_stopped() && !state.stroke.is_empty() {
+                let stroke = std::mem::take(&mut state.stroke);
+                if stroke.len() > 1 {
+                    let fitted = self.curve.splice_fit(&stroke, self.draw_smoothness);
+                    state.dragging = None;
+                    state.selected = None;
+                    state.selection = fitted.collect();
+                }
+            }
+        }
+
+        let mut handle_secondary_clicked = false;
+
+        if (response.clicked() || response.secondary_clicked() || response.dragged())
+            && response.hover_pos().is_some()
+            && state.dragging.is_none()
+            && !self.draw_mode
+        {
+            let pos = response.hover_pos().unwrap();
+
+            let positions = self.curve.point_positions();
+            let mut handles: Vec<_> = positions
+                .iter()
+                .enumerate()
+                .map(|(index, &pos)| {
+                    (
+                        DragTarget::Handle,
+                        index,
+                        CurveEditor::normalized_to_plot_coords(plot_rect, pos),
+                    )
+                })
+                .collect();
+
+            if let Some((selected, selected_pos, left, right)) = self.selected_point(state.selected)
+            {
+                let (left_pos, right_pos) =
+                    CurveEditor::get_tangents_plot_coords(plot_rect, selected_pos, left, right);
+
+                handles.push((DragTarget::LeftTangent, selected, left_pos));
+                handles.push((DragTarget::RightTangent, selected, right_pos));
+            }
+
+            let near = handles
+                .iter()
+                .find(|(_, _, handle_pos)| handle_pos.distance(pos).abs() < 15.0);
+
+            if let Some((drag_type, index, _)) = near {
+                // Start dragging?
+                if response.clicked_by(egui::PointerButton::Primary)
+                    || response.dragged_by(egui::PointerButton::Primary)
+                {
+                    state.dragging = Some(*drag_type);
+                    state.selected = Some(*index);
+                }
+
+                // Select handle, shift toggles it in the selection
+                if response.clicked_by(egui::PointerButton::Primary)
+                    && *drag_type == DragTarget::Handle
+                {
+                    if !ui.input(|i| i.modifiers.shift) {
+                        state.selection.clear();
+                    }
+                    if let Some(position) = state.selection.iter().position(|i| i == index) {
+                        state.selection.remove(position);
+                    } else {
+                        state.selection.push(*index);
+                    }
+                }
+
+                // Remove handle?
+                handle_secondary_clicked = response.secondary_clicked();
+                if response.secondary_clicked()
+                    && !self.curve.index_is_first_or_last(*index)
+                    && *drag_type == DragTarget::Handle
+                {
+                    self.curve.remove_point(*index);
+                    state.dragging = None;
+                    state.selected = None;
+                    state.selection.clear();
+                }
+            } else if response.clicked_by(egui::PointerButton::Primary)
+                || response.dragged_by(egui::PointerButton::Primary)
+            {
+                // Add handle
+                let mut new_pos = CurveEditor::plot_to_normalized_coords(plot_rect, pos);
+                if let Some(timeline) = self.timeline {
+                    new_pos.x = timeline.snap(new_pos.x);
+                }
+                if let Ok(index) = self.curve.try_add_point(Point::from_pos(new_pos)) {
+                    state.selected = Some(index);
+                    state.selection = vec![index];
+                }
+            }
+        }
+
+        // Context menu, unless the click removed a handle
+        let menu_open = !handle_secondary_clicked
+            && response
+                .context_menu(|ui| {
+                    let selection = state
+                        .selection
+                        .iter()
+                        .min()
+                        .zip(state.selection.iter().max())
+                        .filter(|(first, last)| first < last)
+                        .map(|(&first, &last)| (first, last));
+
+                    let mut simplified = None;
+                    if ui
+                        .add_enabled(selection.is_some(), egui::Button::new("Simplify selection"))
+                        .clicked()
+                    {
+                        let (first, last) = selection.unwrap();
+                        simplified = Some(self.curve.simplify_range(
+                            first,
+                            last,
+                            self.simplify_tolerance,
+                        ));
+                    }
+                    if ui.button("Simplify curve").clicked() {
+                        simplified = Some(self.curve.simplify(self.simplify_tolerance));
+                    }
+
+                    if let Some(removed) = simplified {
+                        state.message = Some(format!("Removed {removed} keys"));
+                        state.dragging = None;
+                        state.selected = None;
+                        state.selection.clear();
+                        ui.close_menu();
+                    }
+
+                    ui.separator();
+                    if let Some(selection) = CurveEditor::transform_menu(
+                        ui,
+                        self.curve,
+                        &state.selection,
+                        &mut state.transform,
+                    ) {
+                        state.dragging = None;
+                        state.selected = None;
+                        state.selection = selection;
+                    }
+                })
+                .is_some();
+
+        // Stop dragging?
+        if state.dragging.is_some()
+            && (response.drag_stopped() || !response.is_pointer_button_down_on())
+        {
+            state.dragging = None;
+        }
+
+        // Desellect?
+        if response.clicked_elsewhere() && !menu_open {
+            state.selected = None;
+            state.selection.clear();
+        }
+
+        // Handle dragging
+        if let (Some(index), Some(drag_type)) = (state.selected, state.dragging) {
+            // Handle dragging tangents also
+            if let Some(pos) = self.curve.get_position(index) {
+                match drag_type {
+                    DragTarget::Handle => {
+                        let screen_pos = (CurveEditor::normalized_to_plot_coords(plot_rect, pos)
+                            + response.drag_delta())
+                        .clamp(plot_rect.left_top(), plot_rect.right_bottom());
+
+                        if !self.curve.index_is_first_or_last(index) {
+                            let mut new_pos =
+                                CurveEditor::plot_to_normalized_coords(plot_rect, screen_pos);
+
+                            // Deltas smaller than a frame would snap back, follow the pointer
+                            if let Some((timeline, pointer)) =
+                                self.timeline.zip(response.interact_pointer_pos())
+                            {
+                                new_pos.x = timeline.snap(
+                                    CurveEditor::plot_to_normalized_coords(plot_rect, pointer)
+                                        .x
+                                        .clamp(0.0, 1.0),
+                                );
+                            }
+
+                            self.curve.set_position(index, new_pos);
+                        } else {
+                            self.curve.set_position(
+                                index,
+                                pos2(
+                                    pos.x,
+                                    CurveEditor::plot_to_normalized_coords(plot_rect, screen_pos).y,
+                                ),
+                            );
+                        }
+                    }
+                    DragTarget::LeftTangent => {
+                        if !self.curve.index_is_first(index) {
+                            let screen_pos = CurveEditor::normalized_to_plot_coords(plot_rect, pos);
+                            let tangent = self.curve.get_left_tan(index).unwrap();
+                            let (plot_tangent, _) =
+                                CurveEditor::get_tangents_plot_coords(plot_rect, pos, tangent, 0.0);
+
+                            let mut screen_tan = plot_tangent + response.drag_delta();
+                            screen_tan.x = screen_tan.x.min(screen_pos.x);
+
+                            let tangent_dir = (screen_tan - screen_pos).normalized();
+
+                            self.curve
+                                .set_left_tan(index, -tangent_dir.y / tangent_dir.x);
+                        }
+                    }
+                    DragTarget::RightTangent => {
+                        if !self.curve.index_is_last(index) {
+                            let screen_pos = CurveEditor::normalized_to_plot_coords(plot_rect, pos);
+                            let tangent = self.curve.get_right_tan(index).unwrap();
+                            let (_, plot_tangent) =
+                                CurveEditor::get_tangents_plot_coords(plot_rect, pos, 0.0, tangent);
+
+                            let mut screen_tan = plot_tangent + response.drag_delta();
+                            screen_tan.x = screen_tan.x.max(screen_pos.x);
+
+                            let tangent_dir = (screen_tan - screen_pos).normalized();
+
+                            self.curve
+                                .set_right_tan(index, -tangent_dir.y / tangent_dir.x);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Overshoot
+        if self.show_overshoot {
+            let fill = ui.visuals().warn_fg_color.gamma_multiply(0.15);
+            for range in self.curve.overshoot_ranges() {
+                let rect = egui::Rect::from_two_pos(
+                    CurveEditor::normalized_to_plot_coords(plot_rect, pos2(range.min, 0.0)),
+                    CurveEditor::normalized_to_plot_coords(plot_rect, pos2(range.max, 1.0)),
+                );
+
+                ui.painter()
+                    .with_clip_rect(plot_rect)
+                    .rect_filled(rect, 0, fill);
+            }
+        }
+
+        // Frames
+        if let Some(timeline) = self.timeline {
+            CurveEditor::paint_frames(ui, plot_rect, timeline);
+        }
+
+        // Playhead
+        if let Some(offset) = self.playhead {
+            let x = CurveEditor::normalized_to_plot_coords(
+                plot_rect,
+                pos2(offset.clamp(0.0, 1.0), 0.0),
+            )
+            .x;
+            ui.painter().with_clip_rect(plot_rect).vline(
+                x,
+                plot_rect.y_range(),
+                ui.visuals().selection.stroke,
+            );
+        }
+
+        let mut points = vec![];
+        let mut offset = 0.0;
+        let step = 0.001;
+        while offset < 1.0 {
+            points.push(pos2(offset, self.curve.sample(offset)));
+
+            offset += step;
+        }
+        ui.painter()
+            .with_clip_rect(plot_rect)
+            .add(egui::epaint::PathShape::line(
+                points
+                    .iter()
+                    .map(|&pos| CurveEditor::normalized_to_plot_coords(plot_rect, pos))
+                    .collect(),
+                ui.visuals().widgets.noninteractive.fg_stroke,
+            ));
+
+        // Stroke being sketched
+        if state.stroke.len() > 1 {
+            ui.painter()
+                .with_clip_rect(plot_rect)
+                .add(egui::epaint::PathShape::line(
+                    state
+                        .stroke
+                        .iter()
+                        .map(|&pos| CurveEditor::normalized_to_plot_coords(plot_rect, pos))
+                        .collect(),
+                    ui.visuals().selection.stroke,
+                ));
+        }
+
+        let visuals = ui.style().interact(&response);
+
+        // Draw tangents
+        if let Some((_, pos, left, right)) = self.selected_point(state.selected) {
+            let plot_pos = CurveEditor::normalized_to_plot_coords(plot_rect, pos);
+            let (plot_left, plot_right) =
+                CurveEditor::get_tangents_plot_coords(plot_rect, pos, left, right);
+
+            ui.painter()
+                .with_clip_rect(plot_rect)
+                .line_segment([plot_left, plot_pos], visuals.fg_stroke);
+            ui.painter()
+                .with_clip_rect(plot_rect)
+                .line_segment([plot_right, plot_pos], visuals.fg_stroke);
+
+            ui.painter()
+                .with_clip_rect(plot_rect)
+                .add(egui::epaint::CircleShape {
+                    center: plot_left,
+                    radius: 3.5,
+                    fill: visuals.bg_fill,
+                    stroke: visuals.fg_stroke,
+                });
+            ui.painter()
+                .with_clip_rect(plot_rect)
+                .add(egui::epaint::CircleShape {
+                    center: plot_right,
+                    radius: 3.5,
+                    fill: visuals.bg_fill,
+                    stroke: visuals.fg_stroke,
+                });
+        }
+
+        for (index, &handle_pos) in self.curve.point_positions().iter().enumerate() {
+            let fill = if state.selection.contains(&index) {
+                ui.visuals().selection.bg_fill
+            } else {
+                visuals.bg_fill
+            };
+
+            ui.painter()
+                .with_clip_rect(plot_rect)
+                .add(egui::epaint::CircleShape {
+                    center: CurveEditor::normalized_to_plot_coords(plot_rect, handle_pos),
+                    radius: 5.0,
+                    fill,
+                    stroke: visuals.fg_stroke,
+                });
+        }
+
+        // Result of the last command
+        if let Some(message) = &state.message {
+            ui.painter().with_clip_rect(plot_rect).text(
+                plot_rect.left_bottom() + egui::vec2(4.0, -4.0),
+                egui::Align2::LEFT_BOTTOM,
+                message,
+                egui::FontId::proportional(11.0),
+                ui.visuals().weak_text_color(),
+            );
+        }
+
+        CurveEditor::store_state(ui.ctx(), id, state);
+
+        ui.advance_cursor_after_rect(complete_rect);
+
+        response
+    }
+}
@@ -0,0 +1,534 @@
+// Audio envelopes, a curve split into attack, decay, sustain and release.
+//
+// While a note is held the sampler loops the sustain region, or holds its value if the region is
+// empty. Releasing a note plays the release segment from wherever the envelope is, rescaled so that
+// it starts at the current level and still ends at the last value of the curve.
+
+use crate::math::{pos2, Rangef};
+use crate::prelude::*;
+use crate::{Curve, ParseError, Point};
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, serde::Deserialize, serde::Serialize)]
+pub enum EnvelopeStage {
+    Attack,
+    Decay,
+    Sustain,
+    Release,
+}
+
+impl EnvelopeStage {
+    pub const ALL: [Self; 4] = [Self::Attack, Self::Decay, Self::Sustain, Self::Release];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EnvelopeStage::Attack => "Attack",
+            EnvelopeStage::Decay => "Decay",
+            EnvelopeStage::Sustain => "Sustain",
+            EnvelopeStage::Release => "Release",
+        }
+    }
+}
+
+/// A [`Curve`] over `duration` seconds, with the offsets where its stages end.
+///
+/// Deserializing fails on durations that are not positive and finite, and on stages that are out of
+/// order or outside `0..=1`.
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+#[serde(try_from = "EnvelopeData")]
+pub struct Envelope {
+    curve: Curve,
+    duration: f32,
+    attack_end: f32,
+    sustain: Rangef,
+}
+
+/// The serialized fields of an [`Envelope`], checked before use.
+#[derive(serde::Deserialize)]
+struct EnvelopeData {
+    curve: Curve,
+    duration: f32,
+    attack_end: f32,
+    sustain: Rangef,
+}
+
+impl TryFrom<EnvelopeData> for Envelope {
+    type Error = ParseError;
+
+    fn try_from(data: EnvelopeData) -> Result<Self, ParseError> {
+        if !(data.duration.is_finite() && data.duration > 0.0) {
+            return Err(ParseError::new(format!(
+                "invalid duration `{}`, it must be positive",
+                data.duration
+            )));
+        }
+
+        let Rangef { min, max } = data.sustain;
+        if !(0.0 <= min && min <= max && max <= 1.0) {
+            return Err(ParseError::new(format!(
+                "invalid sustain `{min}..={max}`, it must be ordered and within `0..=1`"
+            )));
+        }
+        if !(0.0..=min).contains(&data.attack_end) {
+            return Err(ParseError::new(format!(
+                "invalid attack end `{}`, it must be within `0..={min}`",
+                data.attack_end
+            )));
+        }
+
+        Ok(Self::new(
+            data.curve,
+            data.duration,
+            data.attack_end,
+            data.sustain,
+        ))
+    }
+}
+
+impl Default for Envelope {
+    fn default() -> Self {
+        Self::adsr(0.01, 0.1, 0.7, 0.3)
+    }
+}
+
+#[allow(unused)]
+impl Envelope {
+    /// Durations are kept positive, offsets are ordered and clamped to `0..=1`.
+    pub fn new(curve: Curve, duration: f32, attack_end: f32, sustain: impl Into<Rangef>) -> Self {
+        let mut envelope = Self {
+            curve,
+            duration: duration.max(f32::EPSILON),
+            attack_end: 0.0,
+            sustain: Rangef::point(0.0),
+        };
+        envelope.set_sustain(sustain);
+        envelope.set_attack_end(attack_end);
+
+        envelope
+    }
+
+    /// The classic shape, times in seconds and the sustain level in `0..=1`.
+    ///
+    /// Rises to `1` over `attack`, falls to `sustain_level` over `decay`, holds it while the note
+    /// is on and falls to `0` over `release`.
+    pub fn adsr(attack: f32, decay: f32, sustain_level: f32, release: f32) -> Self {
+        let (attack, decay, release) = (attack.max(0.0), decay.max(0.0), release.max(0.0));
+        let duration = (attack + decay + release).max(f32::EPSILON);
+
+        let attack_end = attack / duration;
+        let sustain = (attack + decay) / duration;
+        let curve = Curve::from_points([
+            Point::from_pos(pos2(0.0, 0.0)),
+            Point::from_pos(pos2(attack_end, 1.0)),
+            Point::from_pos(pos2(sustain, sustain_level)),
+            Point::from_pos(pos2(1.0, 0.0)),
+        ]);
+
+        Self::new(curve, duration, attack_end, Rangef::point(sustain))
+    }
+
+    pub fn curve(&self) -> &Curve {
+        &self.curve
+    }
+
+    pub fn curve_mut(&mut self) -> &mut Curve {
+        &mut self.curve
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    pub fn set_duration(&mut self, duration: f32) {
+        self.duration = duration.max(f32::EPSILON);
+    }
+
+    pub fn attack_end(&self) -> f32 {
+        self.attack_end
+    }
+
+    /// Also moves the sustain region if it would start before.
+    pub fn set_attack_end(&mut self, attack_end: f32) {
+        self.attack_end = attack_end.clamp(0.0, 1.0);
+        self.sustain.min = self.sustain.min.max(self.attack_end);
+        self.sustain.max = self.sustain.max.max(self.sustain.min);
+    }
+
+    /// The region looped while the note is on.
+    pub fn sustain(&self) -> Rangef {
+        self.sustain
+    }
+
+    /// Also moves the end of the attack if it would come after.
+    pub fn set_sustain(&mut self, sustain: impl Into<Rangef>) {
+        let sustain = sustain.into();
+        let min = sustain.min.clamp(0.0, 1.0);
+        self.sustain = Rangef::new(min, sustain.max.clamp(min, 1.0));
+        self.attack_end = self.attack_end.min(self.sustain.min);
+    }
+
+    /// Offsets covered by a stage.
+    pub fn segment(&self, stage: EnvelopeStage) -> Rangef {
+        match stage {
+            EnvelopeStage::Attack => Rangef::new(0.0, self.attack_end),
+            EnvelopeStage::Decay => Rangef::new(self.attack_end, self.sustain.min),
+            EnvelopeStage::Sustain => self.sustain,
+            EnvelopeStage::Release => Rangef::new(self.sustain.max, 1.0),
+        }
+    }
+
+    /// The stage at `offset`, the later one on a boundary.
+    pub fn stage_at(&self, offset: f32) -> EnvelopeStage {
+        if offset >= self.sustain.max && self.sustain.max < 1.0 {
+            EnvelopeStage::Release
+        } else if offset >= self.sustain.min {
+            EnvelopeStage::Sustain
+        } else if offset >= self.attack_end {
+            EnvelopeStage::Decay
+        } else {
+            EnvelopeStage::Attack
+        }
+    }
+
+    /// Seconds from the end of the sustain region to the end of the curve.
+    pub fn release_duration(&self) -> f32 {
+        (1.0 - self.sustain.max) * self.duration
+    }
+
+    /// The release segment `seconds` after a release at `level`.
+    fn sample_release(&self, level: f32, seconds: f32) -> f32 {
+        let start = self.curve.sample(self.sustain.max);
+        let end = self.curve.sample(1.0);
+        let value = self
+            .curve
+            .sample((self.sustain.max + seconds / self.duration).min(1.0));
+
+        // Same shape, from `level` instead of the value it starts at
+        const EPSILON: f32 = 0.00001;
+        if (start - end).abs() < EPSILON {
+            return level + (value - start);
+        }
+
+        end + (value - end) * (level - end) / (start - end)
+    }
+}
+
+/// Plays an [`Envelope`] for notes turned on and off, one value per audio sample.
+#[derive(Clone)]
+pub struct EnvelopeSampler {
+    envelope: Envelope,
+    /// Seconds into the curve, or into the release once released.
+    time: f64,
+    released_at: Option<f32>,
+    active: bool,
+}
+
+#[allow(unused)]
+impl EnvelopeSampler {
+    pub fn new(envelope: Envelope) -> Self {
+        Self {
+            envelope,
+            time: 0.0,
+            released_at: None,
+            active: false,
+        }
+    }
+
+    pub fn envelope(&self) -> &Envelope {
+        &self.envelope
+    }
+
+    pub fn envelope_mut(&mut self) -> &mut Envelope {
+        &mut self.envelope
+    }
+
+    /// Starts the envelope over from the beginning.
+    pub fn note_on(&mut self) {
+        self.time = 0.0;
+        self.released_at = None;
+        self.active = true;
+    }
+
+    /// Releases from the current level, whatever the stage.
+    pub fn note_off(&mut self) {
+        if self.active && self.released_at.is_none() {
+            self.released_at = Some(self.value());
+            self.time = 0.0;
+        }
+    }
+
+    /// Whether a note is on or still releasing.
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn stage(&self) -> Option<EnvelopeStage> {
+        if !self.active {
+            None
+        } else if self.released_at.is_some() {
+            Some(EnvelopeStage::Release)
+        } else {
+            // Holding an empty sustain region sits on the start of the release
+            Some(match self.envelope.stage_at(self.offset()) {
+                EnvelopeStage::Release => EnvelopeStage::Sustain,
+                stage => stage,
+            })
+        }
+    }
+
+    /// The current value, the last value of the curve once the envelope is done.
+    pub fn value(&self) -> f32 {
+        if !self.active {
+            return self.envelope.curve.sample(1.0);
+        }
+
+        match self.released_at {
+            Some(level) => self.envelope.sample_release(level, self.time as f32),
+            None => self.envelope.curve.sample(self.offset()),
+        }
+    }
+
+    /// Moves time forward by `delta` seconds.
+    pub fn advance(&mut self, delta: f64) {
+        if !self.active || !delta.is_finite() {
+            return;
+        }
+        self.time += delta.max(0.0);
+
+        if self.released_at.is_some() {
+            if self.time >= self.envelope.release_duration() as f64 {
+                self.active = false;
+            }
+
+            return;
+        }
+
+        // Loop the sustain region, or hold its value if it is empty
+        let duration = self.envelope.duration as f64;
+        let start = self.envelope.sustain.min as f64 * duration;
+        let end = self.envelope.sustain.max as f64 * duration;
+        if self.time >= end {
+            self.time = if end > start {
+                start + (self.time - start) % (end - start)
+            } else {
+                start
+            };
+        }
+    }
+
+    /// Writes one value per sample to `buffer`, advancing by the length of the buffer.
+    pub fn fill_buffer(&mut self, sample_rate: f32, buffer: &mut [f32]) {
+        let step = 1.0 / sample_rate.max(f32::EPSILON) as f64;
+
+        for sample in buffer {
+            *sample = self.value();
+            self.advance(step);
+        }
+    }
+
+    fn offset(&self) -> f32 {
+        (self.time / self.envelope.duration as f64) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 0.0001, "{a} != {b}");
+    }
+
+    fn line() -> Curve {
+        Curve::from_points([
+            Point::from_pos(pos2(0.0, 0.0)),
+            Point::from_pos(pos2(1.0, 1.0)),
+        ])
+    }
+
+    /// Attack and decay over 0.1 seconds each, sustain at 0.5 and release over 0.2 seconds.
+    fn adsr() -> EnvelopeSampler {
+        EnvelopeSampler::new(Envelope::adsr(0.1, 0.1, 0.5, 0.2))
+    }
+
+    #[test]
+    fn builds_adsr() {
+        let envelope = Envelope::adsr(0.1, 0.1, 0.5, 0.2);
+
+        assert_close(envelope.duration(), 0.4);
+        assert_close(envelope.attack_end(), 0.25);
+        assert_eq!(envelope.sustain(), Rangef::point(0.5));
+        assert_close(envelope.release_duration(), 0.2);
+        assert_eq!(envelope.stage_at(0.0), EnvelopeStage::Attack);
+        assert_eq!(envelope.stage_at(0.25), EnvelopeStage::Decay);
+        assert_eq!(envelope.stage_at(0.5), EnvelopeStage::Release);
+        assert_eq!(
+            envelope.segment(EnvelopeStage::Release),
+            Rangef::new(0.5, 1.0)
+        );
+
+        // Zero times still give a usable envelope
+        let envelope = Envelope::adsr(0.0, 0.0, 0.5, 0.0);
+        assert!(envelope.duration() > 0.0);
+    }
+
+    #[test]
+    fn orders_stages() {
+        let mut envelope = Envelope::new(line(), -1.0, 0.8, Rangef::new(0.5, 0.6));
+        assert!(envelope.duration() > 0.0);
+        assert_eq!(envelope.attack_end(), 0.8);
+        assert_eq!(envelope.sustain(), Rangef::point(0.8));
+
+        envelope.set_sustain(Rangef::new(-1.0, 2.0));
+        assert_eq!(envelope.sustain(), Rangef::new(0.0, 1.0));
+        assert_eq!(envelope.attack_end(), 0.0);
+        assert_eq!(envelope.stage_at(1.0), EnvelopeStage::Sustain);
+    }
+
+    #[test]
+    fn deserializes_checked_envelopes() {
+        let envelope: Envelope =
+            serde_json::from_str(&serde_json::to_string(&Envelope::default()).unwrap()).unwrap();
+        let default = Envelope::default();
+        assert_eq!(envelope.duration(), default.duration());
+        assert_eq!(envelope.attack_end(), default.attack_end());
+        assert_eq!(envelope.sustain(), default.sustain());
+
+        let text = serde_json::to_string(&Envelope::new(line(), 1.0, 0.25, Rangef::new(0.5, 0.75)))
+            .unwrap();
+        for (field, value) in [
+            ("duration", "0.0"),
+            ("duration", "-1.0"),
+            ("attack_end", "0.6"),
+            ("attack_end", "-0.1"),
+            ("sustain", r#"{"min": 0.75, "max": 0.5}"#),
+            ("sustain", r#"{"min": -0.5, "max": 0.5}"#),
+            ("sustain", r#"{"min": 0.5, "max": 1.5}"#),
+        ] {
+            let mut json: serde_json::Value = serde_json::from_str(&text).unwrap();
+            json[field] = serde_json::from_str(value).unwrap();
+            let error = serde_json::from_value::<Envelope>(json).err();
+            assert!(error.is_some(), "{field}: {value}");
+        }
+
+        let text = ron::to_string(&Envelope::new(line(), 2.0, 0.0, Rangef::point(0.5)))
+            .unwrap()
+            .replace("duration:2.0", "duration:inf");
+        let error = ron::from_str::<Envelope>(&text).err().unwrap();
+        assert!(error.to_string().contains("invalid duration"), "{error}");
+    }
+
+    #[test]
+    fn holds_an_empty_sustain() {
+        let mut sampler = adsr();
+        assert!(!sampler.is_active());
+        assert_eq!(sampler.stage(), None);
+
+        sampler.note_on();
+        sampler.advance(0.05);
+        assert_eq!(sampler.stage(), Some(EnvelopeStage::Attack));
+        assert_close(sampler.value(), 0.5);
+
+        sampler.advance(0.1);
+        assert_eq!(sampler.stage(), Some(EnvelopeStage::Decay));
+        assert_close(sampler.value(), 0.75);
+
+        sampler.advance(10.0);
+        assert_eq!(sampler.stage(), Some(EnvelopeStage::Sustain));
+        assert_close(sampler.value(), 0.5);
+    }
+
+    #[test]
+    fn loops_the_sustain_region() {
+        let mut sampler =
+            EnvelopeSampler::new(Envelope::new(line(), 1.0, 0.0, Rangef::new(0.25, 0.75)));
+        sampler.note_on();
+
+        sampler.advance(0.75);
+        assert_close(sampler.value(), 0.25);
+        sampler.advance(0.3);
+        assert_close(sampler.value(), 0.55);
+        sampler.advance(1.0);
+        assert_close(sampler.value(), 0.55);
+        assert_eq!(sampler.stage(), Some(EnvelopeStage::Sustain));
+    }
+
+    #[test]
+    fn releases_from_the_current_level() {
+        let mut sampler = adsr();
+        sampler.note_on();
+        sampler.advance(1.0);
+        sampler.note_off();
+        assert_eq!(sampler.stage(), Some(EnvelopeStage::Release));
+        assert_close(sampler.value(), 0.5);
+
+        sampler.advance(0.1);
+        assert_close(sampler.value(), 0.25);
+
+        // Releasing twice changes nothing
+        sampler.note_off();
+        assert_close(sampler.value(), 0.25);
+
+        sampler.advance(0.11);
+        assert!(!sampler.is_active());
+        assert_eq!(sampler.value(), 0.0);
+
+        // Released during the attack, the release is scaled down
+        sampler.note_on();
+        sampler.advance(0.025);
+        sampler.note_off();
+        assert_close(sampler.value(), 0.25);
+        sampler.advance(0.1);
+        assert_close(sampler.value(), 0.125);
+    }
+
+    #[test]
+    fn releases_flat_and_empty_segments() {
+        let flat = Curve::from_points([
+            Point::from_pos(pos2(0.0, 0.5)),
+            Point::from_pos(pos2(1.0, 0.5)),
+        ]);
+        let mut sampler = EnvelopeSampler::new(Envelope::new(flat, 1.0, 0.0, Rangef::point(0.5)));
+        sampler.note_on();
+        sampler.note_off();
+        sampler.advance(0.25);
+        assert_close(sampler.value(), 0.5);
+
+        // Without a release the note ends right away
+        let mut sampler =
+            EnvelopeSampler::new(Envelope::new(line(), 1.0, 0.0, Rangef::new(0.5, 1.0)));
+        sampler.note_on();
+        sampler.advance(0.9);
+        sampler.note_off();
+        sampler.advance(0.0);
+        assert!(!sampler.is_active());
+        assert_eq!(sampler.value(), 1.0);
+    }
+
+    #[test]
+    fn ignores_invalid_time() {
+        let mut sampler = adsr();
+        sampler.advance(1.0);
+        sampler.note_off();
+        assert!(!sampler.is_active());
+
+        sampler.note_on();
+        sampler.advance(f64::NAN);
+        sampler.advance(f64::INFINITY);
+        sampler.advance(-1.0);
+        assert_eq!(sampler.value(), 0.0);
+        assert_eq!(sampler.stage(), Some(EnvelopeStage::Attack));
+    }
+
+    #[test]
+    fn fills_buffers() {
+        let mut sampler = adsr();
+        sampler.note_on();
+
+        let mut buffer = [0.0; 5];
+        sampler.fill_buffer(100.0, &mut buffer);
+        for (i, value) in buffer.iter().enumerate() {
+            assert_close(*value, i as f32 * 0.1);
+        }
+        assert_close(sampler.value(), 0.5);
+    }
+}
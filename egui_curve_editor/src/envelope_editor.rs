@@ -0,0 +1,187 @@
+use egui::NumExt;
+
+use crate::math::Rangef;
+use crate::{CurveEditor, Envelope, EnvelopeStage};
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq, Debug)]
+enum Boundary {
+    AttackEnd,
+    SustainStart,
+    SustainEnd,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Default)]
+struct EnvelopeEditorState {
+    dragging: Option<Boundary>,
+}
+
+/// Edits an [`Envelope`], its stages in a strip above a [`CurveEditor`] of its curve.
+///
+/// Dragging the boundaries in the strip moves the stages, the sustain region is shaded.
+pub struct EnvelopeEditor<'a> {
+    envelope: &'a mut Envelope,
+    width: Option<f32>,
+    height: Option<f32>,
+    strip_height: f32,
+}
+
+#[allow(unused)]
+impl<'a> EnvelopeEditor<'a> {
+    const GRAB_DISTANCE: f32 = 6.0;
+
+    pub fn new(envelope: &'a mut Envelope) -> Self {
+        Self {
+            envelope,
+            width: None,
+            height: None,
+            strip_height: 18.0,
+        }
+    }
+
+    pub fn with_width(self, width: f32) -> Self {
+        Self {
+            width: Some(width),
+            ..self
+        }
+    }
+
+    /// Height of the curve editor, the strip comes on top.
+    pub fn with_height(self, height: f32) -> Self {
+        Self {
+            height: Some(height),
+            ..self
+        }
+    }
+
+    pub fn with_strip_height(self, strip_height: f32) -> Self {
+        Self {
+            strip_height,
+            ..self
+        }
+    }
+
+    fn load_state(ctx: &egui::Context, id: egui::Id) -> Option<EnvelopeEditorState> {
+        ctx.data_mut(|d| d.get_persisted(id))
+    }
+
+    fn store_state(ctx: &egui::Context, id: egui::Id, state: EnvelopeEditorState) {
+        ctx.data_mut(|d| d.insert_persisted(id, state));
+    }
+
+    fn boundaries(&self) -> [(Boundary, f32); 3] {
+        let sustain = self.envelope.sustain();
+
+        [
+            (Boundary::AttackEnd, self.envelope.attack_end()),
+            (Boundary::SustainStart, sustain.min),
+            (Boundary::SustainEnd, sustain.max),
+        ]
+    }
+
+    fn set_boundary(&mut self, boundary: Boundary, offset: f32) {
+        let sustain = self.envelope.sustain();
+
+        match boundary {
+            Boundary::AttackEnd => self.envelope.set_attack_end(offset),
+            Boundary::SustainStart => self
+                .envelope
+                .set_sustain(Rangef::new(offset, sustain.max.max(offset))),
+            Boundary::SustainEnd => self
+                .envelope
+                .set_sustain(Rangef::new(sustain.min, offset.max(sustain.min))),
+        }
+    }
+}
+
+impl egui::Widget for EnvelopeEditor<'_> {
+    fn ui(mut self, ui: &mut egui::Ui) -> egui::Response {
+        let width = self
+            .width
+            .unwrap_or_else(|| ui.available_size_before_wrap().x)
+            .at_least(Self::GRAB_DISTANCE * 4.0);
+
+        let id = ui.next_auto_id();
+        let (rect, response) = ui.allocate_exact_size(
+            egui::vec2(width, self.strip_height),
+            egui::Sense::click_and_drag(),
+        );
+        ui.ctx().check_for_id_clash(id, rect, "Envelope");
+
+        let x = |offset: f32| rect.left() + offset * rect.width();
+        let offset_at = |x: f32| ((x - rect.left()) / rect.width()).clamp(0.0, 1.0);
+
+        let mut state = Self::load_state(ui.ctx(), id).unwrap_or_default();
+
+        // Start dragging the nearest boundary, the later one when they overlap
+        if response.drag_started_by(egui::PointerButton::Primary) {
+            if let Some(pos) = response.interact_pointer_pos() {
+                state.dragging = self
+                    .boundaries()
+                    .into_iter()
+                    .rev()
+                    .filter(|(_, offset)| (x(*offset) - pos.x).abs() < Self::GRAB_DISTANCE)
+                    .min_by(|a, b| (x(a.1) - pos.x).abs().total_cmp(&(x(b.1) - pos.x).abs()))
+                    .map(|(boundary, _)| boundary);
+            }
+        }
+
+        // Stop dragging?
+        if state.dragging.is_some()
+            && (response.drag_stopped() || !response.is_pointer_button_down_on())
+        {
+            state.dragging = None;
+        }
+
+        // Handle dragging
+        if let (Some(boundary), Some(pos)) = (state.dragging, response.interact_pointer_pos()) {
+            self.set_boundary(boundary, offset_at(pos.x));
+        }
+
+        let painter = ui.painter().with_clip_rect(rect);
+        let visuals = ui.style().interact(&response);
+
+        // Stages
+        for stage in EnvelopeStage::ALL {
+            let segment = self.envelope.segment(stage);
+            let segment_rect =
+                egui::Rect::from_x_y_ranges(x(segment.min)..=x(segment.max), rect.y_range());
+            if segment_rect.width() <= 0.0 {
+                continue;
+            }
+
+            let fill = if stage == EnvelopeStage::Sustain {
+                ui.visuals().selection.bg_fill.gamma_multiply(0.3)
+            } else {
+                ui.visuals().extreme_bg_color
+            };
+            painter.rect_filled(segment_rect, 0, fill);
+
+            painter.with_clip_rect(segment_rect).text(
+                segment_rect.center(),
+                egui::Align2::CENTER_CENTER,
+                stage.name(),
+                egui::FontId::proportional(11.0),
+                ui.visuals().text_color(),
+            );
+        }
+
+        for (boundary, offset) in self.boundaries() {
+            let stroke = if state.dragging == Some(boundary) {
+                egui::Stroke::new(2.0, ui.visuals().selection.bg_fill)
+            } else {
+                visuals.fg_stroke
+            };
+            painter.vline(x(offset), rect.y_range(), stroke);
+        }
+
+        Self::store_state(ui.ctx(), id, state);
+
+        let mut editor = CurveEditor::new(self.envelope.curve_mut()).with_width(width);
+        if let Some(height) = self.height {
+            editor = editor.with_height(height);
+        }
+        ui.add(editor);
+
+        response
+    }
+}
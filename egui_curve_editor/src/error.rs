@@ -0,0 +1,74 @@
+use crate::prelude::*;
+
+/// Why an edit or query on a [`Curve`](crate::Curve) was rejected.
+///
+/// Values are reported in the precision of the curve, `f64` for a [`Curve64`](crate::Curve64).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CurveError<S = f32> {
+    /// The curve has no points.
+    Empty,
+    /// There is no point at `index`, the curve has `len` points.
+    IndexOutOfBounds { index: usize, len: usize },
+    /// Moving the point at `index` to `x` would pass over its neighbor at `neighbor`.
+    CrossesNeighbor { index: usize, neighbor: usize, x: S },
+    /// The value is NaN or infinite.
+    NonFinite(S),
+}
+
+impl<S: core::fmt::Display> core::fmt::Display for CurveError<S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            CurveError::Empty => write!(f, "the curve has no points"),
+            CurveError::IndexOutOfBounds { index, len } => {
+                write!(
+                    f,
+                    "point {index} does not exist, the curve has {len} points"
+                )
+            }
+            CurveError::CrossesNeighbor { index, neighbor, x } => {
+                write!(
+                    f,
+                    "moving point {index} to x = {x} would cross point {neighbor}"
+                )
+            }
+            CurveError::NonFinite(value) => write!(f, "{value} is not a finite number"),
+        }
+    }
+}
+
+impl<S: core::fmt::Debug + core::fmt::Display> core::error::Error for CurveError<S> {}
+
+/// Why a curve could not be read from an external format.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// The 1-based line the error was found on, if it is tied to one.
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl ParseError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            line: None,
+            message: message.into(),
+        }
+    }
+
+    pub fn at_line(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line: Some(line),
+            message: message.into(),
+        }
+    }
+}
+
+impl core::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {line}: {}", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl core::error::Error for ParseError {}
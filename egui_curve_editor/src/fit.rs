@@ -0,0 +1,304 @@
+// Fitting of sampled data, after Schneider's "An Algorithm for Automatically Fitting Digitized
+// Curves" (Graphics Gems, 1990).
+//
+// A segment of `Curve` has its control points at thirds in x, so only the two end tangents are left
+// to fit, which is a linear least-squares problem. Segments missing the tolerance are split at their
+// worst sample, with a shared tangent estimated from the samples around it.
+
+use core::ops::Range;
+
+use crate::curve::bezier_interpolate;
+use crate::math::{Float, Pos2};
+use crate::prelude::*;
+use crate::{CurveT, PointT, TangentMode};
+
+struct Segment<S> {
+    start: usize,
+    end: usize,
+    left_tan: S,
+    right_tan: S,
+}
+
+#[allow(unused)]
+impl<S: Float> CurveT<S> {
+    /// Approximates `samples` with as few points as the recursive splitting finds.
+    ///
+    /// Samples are in curve space and in any order, `tolerance` is the largest distance in y allowed
+    /// between a sample and the curve.
+    pub fn fit(samples: &[Pos2<S>], tolerance: S) -> Self {
+        let mut samples: Vec<Pos2<S>> = samples
+            .iter()
+            .copied()
+            .filter(|sample| sample.x.is_finite() && sample.y.is_finite())
+            .collect();
+        samples.sort_by(|a, b| a.x.total_cmp(&b.x));
+        samples.dedup_by(|a, b| a.x == b.x);
+
+        match samples[..] {
+            [] => return Self::default(),
+            [sample] => return Self::from_points([PointT::from_pos(sample)]),
+            _ => {}
+        }
+
+        let mut segments = vec![];
+        let mut pending = vec![(0, samples.len() - 1, None, None)];
+        while let Some((start, end, left, right)) = pending.pop() {
+            let (left_tan, right_tan) = fit_tangents(&samples[start..=end], left, right);
+            let (error, worst) = max_error(&samples[start..=end], left_tan, right_tan);
+
+            if error <= tolerance || end - start < 2 {
+                segments.push(Segment {
+                    start,
+                    end,
+                    left_tan,
+                    right_tan,
+                });
+
+                continue;
+            }
+
+            // Split at the worst sample, keeping the curve smooth through it
+            let split = start + worst.clamp(1, end - start - 1);
+            let tangent = estimate_tangent(&samples, split);
+            pending.push((split, end, Some(tangent), right));
+            pending.push((start, split, left, Some(tangent)));
+        }
+
+        let mut points = vec![];
+        for (i, segment) in segments.iter().enumerate() {
+            let left_tan = if i == 0 {
+                segment.left_tan
+            } else {
+                segments[i - 1].right_tan
+            };
+            points.push(PointT::new(
+                samples[segment.start],
+                left_tan,
+                segment.left_tan,
+                TangentMode::Free,
+                TangentMode::Free,
+            ));
+        }
+        let last = segments.last().unwrap();
+        points.push(PointT::new(
+            samples[last.end],
+            last.right_tan,
+            last.right_tan,
+            TangentMode::Free,
+            TangentMode::Free,
+        ));
+
+        Self::from_points(points)
+    }
+
+    /// Replaces the points within the x range of `samples` with a fit of them, see [`CurveT::fit`].
+    ///
+    /// Returns the indices of the fitted points.
+    pub fn splice_fit(&mut self, samples: &[Pos2<S>], tolerance: S) -> Range<usize> {
+        let fitted = Self::fit(samples, tolerance);
+        let (Some(first), Some(last)) = (fitted.points().first(), fitted.points().last()) else {
+            return 0..0;
+        };
+        let (start, end) = (first.pos().x, last.pos().x);
+
+        let before = self.points().iter().filter(|point| point.pos().x < start);
+        let after = self.points().iter().filter(|point| point.pos().x > end);
+        let index = before.clone().count();
+
+        let monotone = self.monotone_constraint();
+        *self = Self::from_points(
+            before
+                .chain(fitted.points())
+                .chain(after)
+                .copied()
+                .collect::<Vec<PointT<S>>>(),
+        );
+        self.set_monotone_constraint(monotone);
+
+        index..index + fitted.points().len()
+    }
+}
+
+/// Least-squares tangents for a segment through the first and last sample.
+///
+/// Tangents that are given are kept as they are.
+pub(crate) fn fit_tangents<S: Float>(
+    samples: &[Pos2<S>],
+    left: Option<S>,
+    right: Option<S>,
+) -> (S, S) {
+    let epsilon = S::from_f32(0.000001);
+    let three = S::from_f32(3.0);
+
+    let (a, b) = (samples[0], samples[samples.len() - 1]);
+    let d = b.x - a.x;
+    let secant = (b.y - a.y) / d;
+
+    // With control heights `a.y + p` and `b.y - q`, the bézier is linear in `p` and `q`
+    let (mut b1b1, mut b1b2, mut b2b2, mut b1r, mut b2r) =
+        (S::ZERO, S::ZERO, S::ZERO, S::ZERO, S::ZERO);
+    for sample in samples {
+        let t = (sample.x - a.x) / d;
+        let omt = S::ONE - t;
+        let b1 = three * omt * omt * t;
+        let b2 = three * omt * t * t;
+        let base = bezier_interpolate(a.y, a.y, b.y, b.y, t);
+        let r = sample.y - base;
+
+        b1b1 += b1 * b1;
+        b1b2 += b1 * b2;
+        b2b2 += b2 * b2;
+        b1r += b1 * r;
+        b2r += b2 * r;
+    }
+
+    let to_offset = |tangent: S| tangent * d / three;
+    let to_tangent = |offset: S| offset * three / d;
+
+    match (left, right) {
+        (Some(left), Some(right)) => (left, right),
+        (Some(left), None) if b2b2 > epsilon => {
+            let p = to_offset(left);
+            (left, to_tangent((b1b2 * p - b2r) / b2b2))
+        }
+        (None, Some(right)) if b1b1 > epsilon => {
+            let q = to_offset(right);
+            (to_tangent((b1r + b1b2 * q) / b1b1), right)
+        }
+        (None, None) => {
+            let det = b1b1 * b2b2 - b1b2 * b1b2;
+            if det.abs() < epsilon {
+                return (secant, secant);
+            }
+
+            let p = (b1r * b2b2 - b1b2 * b2r) / det;
+            let q = (b1r * b1b2 - b1b1 * b2r) / det;
+            (to_tangent(p), to_tangent(q))
+        }
+        (left, right) => (left.unwrap_or(secant), right.unwrap_or(secant)),
+    }
+}
+
+/// Returns the largest error in y and the index of the sample it occurs at.
+pub(crate) fn max_error<S: Float>(samples: &[Pos2<S>], left_tan: S, right_tan: S) -> (S, usize) {
+    let three = S::from_f32(3.0);
+    let (a, b) = (samples[0], samples[samples.len() - 1]);
+    let d = b.x - a.x;
+    let yac = a.y + d / three * left_tan;
+    let ybc = b.y - d / three * right_tan;
+
+    samples
+        .iter()
+        .enumerate()
+        .map(|(i, sample)| {
+            let t = (sample.x - a.x) / d;
+            (
+                (bezier_interpolate(a.y, yac, ybc, b.y, t) - sample.y).abs(),
+                i,
+            )
+        })
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .unwrap_or((S::ZERO, 0))
+}
+
+fn estimate_tangent<S: Float>(samples: &[Pos2<S>], index: usize) -> S {
+    let (a, b) = (samples[index - 1], samples[index + 1]);
+
+    (b.y - a.y) / (b.x - a.x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::{pos2, Pos2};
+    use crate::{Curve, Point};
+
+    fn sampled(f: impl Fn(f32) -> f32, count: usize) -> Vec<Pos2> {
+        (0..=count)
+            .map(|i| {
+                let x = i as f32 / count as f32;
+                pos2(x, f(x))
+            })
+            .collect()
+    }
+
+    fn max_distance(curve: &Curve, samples: &[Pos2]) -> f32 {
+        samples
+            .iter()
+            .map(|sample| (curve.sample(sample.x) - sample.y).abs())
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn fits_a_line_with_two_points() {
+        let samples = sampled(|x| 0.2 + 0.6 * x, 20);
+        let curve = Curve::fit(&samples, 0.001);
+
+        assert_eq!(curve.points().len(), 2);
+        assert!(max_distance(&curve, &samples) < 0.001);
+    }
+
+    #[test]
+    fn fits_within_tolerance() {
+        let samples = sampled(|x| 0.5 + 0.4 * (x * 12.0).sin(), 200);
+
+        for tolerance in [0.05, 0.01, 0.002] {
+            let curve = Curve::fit(&samples, tolerance);
+            assert!(max_distance(&curve, &samples) <= tolerance + 0.0001);
+        }
+
+        // Tighter tolerances need more points
+        assert!(
+            Curve::fit(&samples, 0.002).points().len() > Curve::fit(&samples, 0.05).points().len()
+        );
+    }
+
+    #[test]
+    fn sorts_and_filters_samples() {
+        let mut samples = sampled(|x| x * x, 30);
+        samples.reverse();
+        samples.push(pos2(f32::NAN, 0.5));
+        samples.push(pos2(0.5, f32::INFINITY));
+        samples.push(samples[3]);
+
+        let curve = Curve::fit(&samples, 0.005);
+        assert!(curve.is_monotonic());
+        assert!(max_distance(&curve, &sampled(|x| x * x, 30)) < 0.006);
+    }
+
+    #[test]
+    fn fits_degenerate_samples() {
+        assert!(Curve::fit(&[], 0.01).points().is_empty());
+        assert!(Curve::fit(&[pos2(f32::NAN, 0.0)], 0.01).points().is_empty());
+
+        let single = Curve::fit(&[pos2(0.3, 0.7)], 0.01);
+        assert_eq!(single.points().len(), 1);
+        assert_eq!(single.get_position(0), Some(pos2(0.3, 0.7)));
+
+        let pair = Curve::fit(&[pos2(0.0, 1.0), pos2(1.0, 0.0)], 0.01);
+        assert!((pair.sample(0.5) - 0.5).abs() < 0.0001);
+    }
+
+    #[test]
+    fn splices_fit_into_curve() {
+        let mut curve = Curve::from_points([
+            Point::from_pos(pos2(0.0, 0.0)),
+            Point::from_pos(pos2(0.3, 0.8)),
+            Point::from_pos(pos2(0.6, 0.1)),
+            Point::from_pos(pos2(1.0, 1.0)),
+        ]);
+        curve.set_monotone_constraint(true);
+
+        let samples: Vec<Pos2> = (0..=10).map(|i| pos2(0.2 + i as f32 * 0.05, 0.5)).collect();
+        let range = curve.splice_fit(&samples, 0.001);
+
+        assert_eq!(range, 1..3);
+        assert_eq!(curve.points().len(), 4);
+        assert_eq!(curve.get_position(1), Some(pos2(0.2, 0.5)));
+        assert_eq!(curve.get_position(2), Some(pos2(0.7, 0.5)));
+        assert!(curve.monotone_constraint());
+
+        assert_eq!(curve.splice_fit(&[], 0.001), 0..0);
+        assert_eq!(curve.points().len(), 4);
+    }
+}
@@ -0,0 +1,250 @@
+// The scalar the curve model is generic over, `f32` or `f64`.
+//
+// Float functions missing from `core` are taken from libm when the standard library is not
+// available. With `std`, the inherent methods of `f32` and `f64` are used, and take precedence over
+// the ones of this trait for concrete types.
+
+use core::fmt::{Debug, Display};
+use core::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+/// A float type curves can be built on, implemented for `f32` and `f64`.
+pub trait Float:
+    Copy
+    + PartialOrd
+    + Default
+    + Debug
+    + Display
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + MulAssign
+    + DivAssign
+    + serde::Serialize
+    + serde::de::DeserializeOwned
+    + 'static
+{
+    const ZERO: Self;
+    const ONE: Self;
+    const INFINITY: Self;
+    const NEG_INFINITY: Self;
+    /// Distance under which two positions are treated as the same.
+    const TOLERANCE: Self;
+
+    fn from_f32(value: f32) -> Self;
+    fn from_f64(value: f64) -> Self;
+    fn to_f32(self) -> f32;
+    fn to_f64(self) -> f64;
+
+    fn is_finite(self) -> bool;
+    fn is_nan(self) -> bool;
+    fn min(self, other: Self) -> Self;
+    fn max(self, other: Self) -> Self;
+    fn clamp(self, min: Self, max: Self) -> Self;
+    fn total_cmp(&self, other: &Self) -> core::cmp::Ordering;
+
+    fn sqrt(self) -> Self;
+    fn cbrt(self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn acos(self) -> Self;
+    fn powf(self, exponent: Self) -> Self;
+    fn hypot(self, other: Self) -> Self;
+    fn abs(self) -> Self;
+    fn signum(self) -> Self;
+    fn round(self) -> Self;
+    fn floor(self) -> Self;
+    fn ceil(self) -> Self;
+    fn fract(self) -> Self;
+
+    fn sin_cos(self) -> (Self, Self);
+}
+
+/// Forwards to the inherent method with `std`, to libm without.
+macro_rules! forward {
+    ($($name:ident($($arg:ident),*) => $libm:ident;)*) => {
+        $(
+            fn $name(self $(, $arg: Self)*) -> Self {
+                #[cfg(feature = "std")]
+                {
+                    <Self>::$name(self $(, $arg)*)
+                }
+                #[cfg(not(feature = "std"))]
+                {
+                    libm::$libm(self $(, $arg)*)
+                }
+            }
+        )*
+    };
+}
+
+impl Float for f32 {
+    const ZERO: Self = 0.0;
+    const ONE: Self = 1.0;
+    const INFINITY: Self = f32::INFINITY;
+    const NEG_INFINITY: Self = f32::NEG_INFINITY;
+    const TOLERANCE: Self = 0.00001;
+
+    fn from_f32(value: f32) -> Self {
+        value
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+
+    fn to_f32(self) -> f32 {
+        self
+    }
+
+    fn to_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn is_finite(self) -> bool {
+        f32::is_finite(self)
+    }
+
+    fn is_nan(self) -> bool {
+        f32::is_nan(self)
+    }
+
+    fn min(self, other: Self) -> Self {
+        f32::min(self, other)
+    }
+
+    fn max(self, other: Self) -> Self {
+        f32::max(self, other)
+    }
+
+    fn clamp(self, min: Self, max: Self) -> Self {
+        f32::clamp(self, min, max)
+    }
+
+    fn total_cmp(&self, other: &Self) -> core::cmp::Ordering {
+        f32::total_cmp(self, other)
+    }
+
+    forward! {
+        sqrt() => sqrtf;
+        cbrt() => cbrtf;
+        sin() => sinf;
+        cos() => cosf;
+        acos() => acosf;
+        powf(exponent) => powf;
+        hypot(other) => hypotf;
+        abs() => fabsf;
+        round() => roundf;
+        floor() => floorf;
+        ceil() => ceilf;
+    }
+
+    fn signum(self) -> Self {
+        if self.is_nan() {
+            self
+        } else {
+            f32::copysign(1.0, self)
+        }
+    }
+
+    fn fract(self) -> Self {
+        #[cfg(feature = "std")]
+        return f32::fract(self);
+        #[cfg(not(feature = "std"))]
+        return self - libm::truncf(self);
+    }
+
+    fn sin_cos(self) -> (Self, Self) {
+        #[cfg(feature = "std")]
+        return f32::sin_cos(self);
+        #[cfg(not(feature = "std"))]
+        return libm::sincosf(self);
+    }
+}
+
+impl Float for f64 {
+    const ZERO: Self = 0.0;
+    const ONE: Self = 1.0;
+    const INFINITY: Self = f64::INFINITY;
+    const NEG_INFINITY: Self = f64::NEG_INFINITY;
+    const TOLERANCE: Self = 0.000000000001;
+
+    fn from_f32(value: f32) -> Self {
+        value as f64
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+
+    fn to_f32(self) -> f32 {
+        self as f32
+    }
+
+    fn to_f64(self) -> f64 {
+        self
+    }
+
+    fn is_finite(self) -> bool {
+        f64::is_finite(self)
+    }
+
+    fn is_nan(self) -> bool {
+        f64::is_nan(self)
+    }
+
+    fn min(self, other: Self) -> Self {
+        f64::min(self, other)
+    }
+
+    fn max(self, other: Self) -> Self {
+        f64::max(self, other)
+    }
+
+    fn clamp(self, min: Self, max: Self) -> Self {
+        f64::clamp(self, min, max)
+    }
+
+    fn total_cmp(&self, other: &Self) -> core::cmp::Ordering {
+        f64::total_cmp(self, other)
+    }
+
+    forward! {
+        sqrt() => sqrt;
+        cbrt() => cbrt;
+        sin() => sin;
+        cos() => cos;
+        acos() => acos;
+        powf(exponent) => pow;
+        hypot(other) => hypot;
+        abs() => fabs;
+        round() => round;
+        floor() => floor;
+        ceil() => ceil;
+    }
+
+    fn signum(self) -> Self {
+        if self.is_nan() {
+            self
+        } else {
+            f64::copysign(1.0, self)
+        }
+    }
+
+    fn fract(self) -> Self {
+        #[cfg(feature = "std")]
+        return f64::fract(self);
+        #[cfg(not(feature = "std"))]
+        return self - libm::trunc(self);
+    }
+
+    fn sin_cos(self) -> (Self, Self) {
+        #[cfg(feature = "std")]
+        return f64::sin_cos(self);
+        #[cfg(not(feature = "std"))]
+        return libm::sincos(self);
+    }
+}
@@ -0,0 +1,377 @@
+// The serialized representation of curves, kept apart from their fields so that those can change
+// without breaking saved data.
+//
+// Version 1, shown in JSON:
+//
+//     {"version": 1, "monotone": false, "keys": [[0.0, 0.0, 0.0, 1.0, "Linear", "Linear"], ...]}
+//
+// Each key is `[x, y, left_tan, right_tan, left_mode, right_mode]`, in ascending x, with the modes
+// `"Linear"` or `"Free"`. Data without a version is the format from before versioning,
+// `{"points": [{"pos": {"x": 0.0, "y": 0.0}, "left_tan": 0.0, ...}], "monotone": false}`, and is
+// migrated when read.
+
+use core::marker::PhantomData;
+
+use serde::de::{Error, IgnoredAny, MapAccess, SeqAccess, Visitor};
+
+use crate::math::{Float, Pos2};
+use crate::prelude::*;
+use crate::{CurveT, ParseError, PointT, TangentMode};
+
+/// The version written by [`CurveData`].
+pub const CURVE_FORMAT_VERSION: u32 = 1;
+
+/// One key, `[x, y, left_tan, right_tan, left_mode, right_mode]`.
+pub type KeyData<S> = (S, S, S, S, TangentMode, TangentMode);
+
+/// The current format of a [`Curve`](crate::Curve) or [`Curve64`](crate::Curve64), with `f32` or `f64` values.
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+pub struct CurveData<S> {
+    pub version: u32,
+    #[serde(default)]
+    pub monotone: bool,
+    pub keys: Vec<KeyData<S>>,
+}
+
+/// Any format that can be read, see [`AnyCurveData::migrate`].
+///
+/// The version is read before the keys, so that data from a newer version is reported as such
+/// rather than as keys of the wrong shape. Data written by [`CurveData`] has it first.
+#[derive(Clone)]
+pub enum AnyCurveData<S> {
+    Versioned(CurveData<S>),
+    Unversioned(UnversionedCurveData<S>),
+}
+
+/// The format from before versioning, the fields of [`Curve`](crate::Curve) as they were then.
+#[derive(Clone, serde::Deserialize)]
+pub struct UnversionedCurveData<S> {
+    pub points: Vec<UnversionedPoint<S>>,
+    #[serde(default)]
+    pub monotone: bool,
+}
+
+#[derive(Clone, serde::Deserialize)]
+pub struct UnversionedPoint<S> {
+    pub pos: UnversionedPos<S>,
+    pub left_tan: S,
+    pub right_tan: S,
+    pub left_mode: TangentMode,
+    pub right_mode: TangentMode,
+}
+
+#[derive(Clone, serde::Deserialize)]
+pub struct UnversionedPos<S> {
+    pub x: S,
+    pub y: S,
+}
+
+impl<S> AnyCurveData<S> {
+    /// Brings the data to [`CURVE_FORMAT_VERSION`], failing on versions newer than it.
+    pub fn migrate(self) -> Result<CurveData<S>, ParseError> {
+        match self {
+            AnyCurveData::Versioned(data) if data.version == CURVE_FORMAT_VERSION => Ok(data),
+            AnyCurveData::Versioned(data) => Err(unsupported_version(data.version)),
+            AnyCurveData::Unversioned(data) => Ok(CurveData {
+                version: CURVE_FORMAT_VERSION,
+                monotone: data.monotone,
+                keys: data
+                    .points
+                    .into_iter()
+                    .map(|point| {
+                        (
+                            point.pos.x,
+                            point.pos.y,
+                            point.left_tan,
+                            point.right_tan,
+                            point.left_mode,
+                            point.right_mode,
+                        )
+                    })
+                    .collect(),
+            }),
+        }
+    }
+}
+
+fn unsupported_version(version: u32) -> ParseError {
+    ParseError::new(format!(
+        "curve format version {version} is not supported, the latest is {CURVE_FORMAT_VERSION}"
+    ))
+}
+
+impl<'de, S: serde::Deserialize<'de>> serde::Deserialize<'de> for AnyCurveData<S> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        const FIELDS: &[&str] = &["version", "monotone", "keys", "points"];
+
+        deserializer.deserialize_struct("CurveData", FIELDS, AnyCurveDataVisitor(PhantomData))
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(field_identifier, rename_all = "lowercase")]
+enum Field {
+    Version,
+    Monotone,
+    Keys,
+    Points,
+    #[serde(other)]
+    Other,
+}
+
+struct AnyCurveDataVisitor<S>(PhantomData<S>);
+
+impl<'de, S: serde::Deserialize<'de>> Visitor<'de> for AnyCurveDataVisitor<S> {
+    type Value = AnyCurveData<S>;
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+        formatter.write_str("curve data")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let check = |version: u32| match version {
+            CURVE_FORMAT_VERSION => Ok(version),
+            _ => Err(A::Error::custom(unsupported_version(version))),
+        };
+
+        let mut version = None;
+        let mut monotone = false;
+        let mut keys = None;
+        let mut points = None;
+        while let Some(field) = map.next_key()? {
+            match field {
+                Field::Version => version = Some(check(map.next_value()?)?),
+                Field::Monotone => monotone = map.next_value()?,
+                Field::Keys => keys = Some(map.next_value()?),
+                Field::Points => points = Some(map.next_value()?),
+                Field::Other => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+
+        match (version, keys, points) {
+            (Some(version), Some(keys), _) => Ok(AnyCurveData::Versioned(CurveData {
+                version,
+                monotone,
+                keys,
+            })),
+            (Some(_), None, _) => Err(A::Error::missing_field("keys")),
+            (None, _, Some(points)) => Ok(AnyCurveData::Unversioned(UnversionedCurveData {
+                points,
+                monotone,
+            })),
+            (None, _, None) => Err(A::Error::missing_field("version")),
+        }
+    }
+
+    /// Formats without field names only hold the current format, in field order.
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let version: u32 = seq
+            .next_element()?
+            .ok_or_else(|| A::Error::invalid_length(0, &self))?;
+        if version != CURVE_FORMAT_VERSION {
+            return Err(A::Error::custom(unsupported_version(version)));
+        }
+        let monotone = seq
+            .next_element()?
+            .ok_or_else(|| A::Error::invalid_length(1, &self))?;
+        let keys = seq
+            .next_element()?
+            .ok_or_else(|| A::Error::invalid_length(2, &self))?;
+
+        Ok(AnyCurveData::Versioned(CurveData {
+            version,
+            monotone,
+            keys,
+        }))
+    }
+}
+
+impl<S: Float> From<CurveT<S>> for CurveData<S> {
+    fn from(curve: CurveT<S>) -> Self {
+        Self {
+            version: CURVE_FORMAT_VERSION,
+            monotone: curve.monotone_constraint(),
+            keys: curve
+                .points()
+                .iter()
+                .map(|point| {
+                    (
+                        point.pos().x,
+                        point.pos().y,
+                        point.left_tan(),
+                        point.right_tan(),
+                        point.left_mode(),
+                        point.right_mode(),
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<S: Float> TryFrom<AnyCurveData<S>> for CurveT<S> {
+    type Error = ParseError;
+
+    fn try_from(data: AnyCurveData<S>) -> Result<Self, ParseError> {
+        let data = data.migrate()?;
+
+        let mut curve = CurveT::from_points(data.keys.into_iter().map(
+            |(x, y, left_tan, right_tan, left_mode, right_mode)| {
+                PointT::new(Pos2::new(x, y), left_tan, right_tan, left_mode, right_mode)
+            },
+        ));
+        curve.set_monotone_constraint(data.monotone);
+
+        Ok(curve)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::{dpos2, pos2};
+    use crate::{Curve, Curve64, Point, Point64};
+
+    fn curve() -> Curve {
+        Curve::from_points([
+            Point::new(
+                pos2(0.0, 0.1),
+                0.0,
+                1.5,
+                TangentMode::Free,
+                TangentMode::Free,
+            ),
+            Point::from_pos(pos2(0.25, 0.75)),
+            Point::new(
+                pos2(1.0, 0.9),
+                -0.5,
+                0.0,
+                TangentMode::Free,
+                TangentMode::Linear,
+            ),
+        ])
+    }
+
+    fn curve64() -> Curve64 {
+        let mut curve = Curve64::from_points([
+            Point64::from_pos(dpos2(0.0, 0.0)),
+            Point64::new(
+                dpos2(0.1, 0.123_456_789_012_345),
+                2.0,
+                2.0,
+                TangentMode::Free,
+                TangentMode::Free,
+            ),
+            Point64::from_pos(dpos2(1.0, 1.0)),
+        ]);
+        curve.set_monotone_constraint(true);
+        curve
+    }
+
+    fn to_ron<T: serde::Serialize>(value: &T) -> String {
+        ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default()).unwrap() + "\n"
+    }
+
+    fn to_json<T: serde::Serialize>(value: &T) -> String {
+        serde_json::to_string(value).unwrap() + "\n"
+    }
+
+    fn assert_same(a: &Curve, b: &Curve) {
+        assert_eq!(to_json(a), to_json(b));
+        assert_eq!(a.monotone_constraint(), b.monotone_constraint());
+    }
+
+    #[test]
+    fn writes_golden_files() {
+        assert_eq!(to_json(&curve()), include_str!("../testdata/curve_v1.json"));
+        assert_eq!(to_ron(&curve()), include_str!("../testdata/curve_v1.ron"));
+        assert_eq!(
+            to_json(&curve64()),
+            include_str!("../testdata/curve64_v1.json")
+        );
+        assert_eq!(
+            to_ron(&curve64()),
+            include_str!("../testdata/curve64_v1.ron")
+        );
+    }
+
+    #[test]
+    fn reads_golden_files() {
+        let json: Curve = serde_json::from_str(include_str!("../testdata/curve_v1.json")).unwrap();
+        let ron: Curve = ron::from_str(include_str!("../testdata/curve_v1.ron")).unwrap();
+        assert_same(&json, &curve());
+        assert_same(&ron, &curve());
+
+        let json: Curve64 =
+            serde_json::from_str(include_str!("../testdata/curve64_v1.json")).unwrap();
+        let ron: Curve64 = ron::from_str(include_str!("../testdata/curve64_v1.ron")).unwrap();
+        assert_eq!(to_json(&json), to_json(&curve64()));
+        assert_eq!(to_json(&ron), to_json(&curve64()));
+        assert!(ron.monotone_constraint());
+
+        // Either precision reads the other's data
+        let curve: Curve =
+            serde_json::from_str(include_str!("../testdata/curve64_v1.json")).unwrap();
+        assert_eq!(to_json(&curve), to_json(&curve64().to_curve()));
+    }
+
+    #[test]
+    fn migrates_unversioned_data() {
+        let json: Curve =
+            serde_json::from_str(include_str!("../testdata/curve_unversioned.json")).unwrap();
+        let ron: Curve = ron::from_str(include_str!("../testdata/curve_unversioned.ron")).unwrap();
+
+        assert_same(&json, &curve());
+        assert_same(&ron, &curve());
+        assert_eq!(to_json(&json), include_str!("../testdata/curve_v1.json"));
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        // Keys of a shape version 1 cannot read still report the version
+        let error = serde_json::from_str::<Curve>(
+            r#"{"version": 2, "keys": [[0.0, 0.0, 0.0, 0.0, 0.5, "Free", "Free"]]}"#,
+        )
+        .err()
+        .unwrap();
+        assert!(
+            error
+                .to_string()
+                .starts_with("curve format version 2 is not supported, the latest is 1"),
+            "{error}"
+        );
+
+        let error = ron::from_str::<Curve>("(version: 3, keys: [])")
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("version 3 is not supported"));
+
+        let data: AnyCurveData<f32> = AnyCurveData::Versioned(CurveData {
+            version: 2,
+            monotone: false,
+            keys: vec![],
+        });
+        assert!(data.migrate().is_err());
+    }
+
+    #[test]
+    fn rejects_incomplete_data() {
+        for text in [
+            r#"{}"#,
+            r#"{"monotone": true}"#,
+            r#"{"version": 1}"#,
+            "[]",
+            "1",
+        ] {
+            assert!(serde_json::from_str::<Curve>(text).is_err(), "{text}");
+        }
+
+        // Unknown fields are skipped, missing monotone is false
+        let curve: Curve =
+            serde_json::from_str(r#"{"version": 1, "name": "fade", "keys": []}"#).unwrap();
+        assert!(curve.points().is_empty());
+        assert!(!curve.monotone_constraint());
+    }
+}
@@ -0,0 +1,451 @@
+// Godot `Curve` resources, as saved in `.tres` files or embedded as sub-resources in scenes.
+//
+// Godot stores points in its own value and domain ranges, they are mapped to `0..=1` on import.
+
+use crate::math::{pos2, Pos2};
+use crate::prelude::*;
+use crate::{Curve, ParseError, Point, TangentMode};
+
+/// A [`Curve`] together with the resource properties Godot keeps around it.
+#[derive(Clone)]
+pub struct GodotCurve {
+    pub curve: Curve,
+    pub min_value: f32,
+    pub max_value: f32,
+    pub min_domain: f32,
+    pub max_domain: f32,
+    pub bake_resolution: usize,
+}
+
+impl Default for GodotCurve {
+    fn default() -> Self {
+        Self {
+            curve: Curve::default(),
+            min_value: 0.0,
+            max_value: 1.0,
+            min_domain: 0.0,
+            max_domain: 1.0,
+            bake_resolution: 100,
+        }
+    }
+}
+
+#[allow(unused)]
+impl GodotCurve {
+    pub fn new(curve: Curve) -> Self {
+        Self {
+            curve,
+            ..Default::default()
+        }
+    }
+
+    /// Reads the first `Curve` resource of a Godot 3 or 4 text resource or scene.
+    pub fn from_tres(text: &str) -> Result<Self, ParseError> {
+        let mut result = Self::default();
+        let mut data = None;
+
+        let mut is_curve_resource = false;
+        let mut in_curve = false;
+        let mut found = false;
+
+        let mut lines = text.lines().enumerate().map(|(i, line)| (i + 1, line));
+        while let Some((line_number, line)) = lines.next() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') {
+                continue;
+            }
+
+            // Section headers
+            if line.starts_with('[') {
+                if found {
+                    break;
+                }
+
+                if line.starts_with("[gd_resource") {
+                    is_curve_resource = line.contains("type=\"Curve\"");
+                } else if line == "[resource]" {
+                    in_curve = is_curve_resource;
+                } else if line.starts_with("[sub_resource") {
+                    in_curve = line.contains("type=\"Curve\"");
+                } else {
+                    in_curve = false;
+                }
+
+                found = in_curve;
+
+                continue;
+            }
+
+            if !in_curve {
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(ParseError::at_line(line_number, "expected `key = value`"));
+            };
+            let key = key.trim();
+            let mut value = value.trim().to_string();
+
+            // Values can span several lines until their brackets are closed
+            while bracket_depth(&value) > 0 {
+                let Some((_, next)) = lines.next() else {
+                    return Err(ParseError::at_line(line_number, "unclosed bracket"));
+                };
+                value.push(' ');
+                value.push_str(next.trim());
+            }
+
+            match key {
+                "min_value" => result.min_value = parse_real(&value, line_number)?,
+                "max_value" => result.max_value = parse_real(&value, line_number)?,
+                "min_domain" => result.min_domain = parse_real(&value, line_number)?,
+                "max_domain" => result.max_domain = parse_real(&value, line_number)?,
+                "bake_resolution" => {
+                    result.bake_resolution = value.parse().map_err(|_| {
+                        ParseError::at_line(line_number, format!("invalid integer `{value}`"))
+                    })?
+                }
+                "_data" => data = Some((line_number, value)),
+                _ => {}
+            }
+        }
+
+        if !found {
+            return Err(ParseError::new("no Curve resource found"));
+        }
+
+        let value_range = result.max_value - result.min_value;
+        let domain_range = result.max_domain - result.min_domain;
+        if value_range <= 0.0 || domain_range <= 0.0 {
+            return Err(ParseError::new("empty value or domain range"));
+        }
+
+        if let Some((line_number, data)) = data {
+            let elements = split_array(&data)
+                .ok_or_else(|| ParseError::at_line(line_number, "`_data` is not an array"))?;
+
+            if elements.len() % 5 != 0 {
+                return Err(ParseError::at_line(
+                    line_number,
+                    "`_data` must hold 5 entries per point",
+                ));
+            }
+
+            let mut points = vec![];
+            for chunk in elements.chunks(5) {
+                let pos = parse_vector2(chunk[0], line_number)?;
+                let left_tan = parse_real(chunk[1], line_number)?;
+                let right_tan = parse_real(chunk[2], line_number)?;
+                let left_mode = parse_mode(chunk[3], line_number)?;
+                let right_mode = parse_mode(chunk[4], line_number)?;
+
+                let scale = domain_range / value_range;
+                points.push(Point::new(
+                    pos2(
+                        (pos.x - result.min_domain) / domain_range,
+                        (pos.y - result.min_value) / value_range,
+                    ),
+                    left_tan * scale,
+                    right_tan * scale,
+                    left_mode,
+                    right_mode,
+                ));
+            }
+
+            result.curve = Curve::from_points(points);
+        }
+
+        Ok(result)
+    }
+
+    /// Writes a Godot 4 text resource holding the curve.
+    pub fn to_tres(&self) -> String {
+        let value_range = self.max_value - self.min_value;
+        let domain_range = self.max_domain - self.min_domain;
+        let scale = value_range / domain_range;
+
+        let data: Vec<String> = self
+            .curve
+            .points()
+            .iter()
+            .map(|point| {
+                let pos = point.pos();
+                format!(
+                    "Vector2({}, {}), {}, {}, {}, {}",
+                    format_number(self.min_domain + pos.x * domain_range),
+                    format_number(self.min_value + pos.y * value_range),
+                    format_real(point.left_tan() * scale),
+                    format_real(point.right_tan() * scale),
+                    mode_to_int(point.left_mode()),
+                    mode_to_int(point.right_mode()),
+                )
+            })
+            .collect();
+
+        let defaults = Self::default();
+
+        // Godot only saves properties that differ from their default
+        let mut text = String::from("[gd_resource type=\"Curve\" format=3]\n\n[resource]\n");
+        if self.min_value != defaults.min_value {
+            text += &format!("min_value = {}\n", format_real(self.min_value));
+        }
+        if self.max_value != defaults.max_value {
+            text += &format!("max_value = {}\n", format_real(self.max_value));
+        }
+        if self.min_domain != defaults.min_domain {
+            text += &format!("min_domain = {}\n", format_real(self.min_domain));
+        }
+        if self.max_domain != defaults.max_domain {
+            text += &format!("max_domain = {}\n", format_real(self.max_domain));
+        }
+        if self.bake_resolution != defaults.bake_resolution {
+            text += &format!("bake_resolution = {}\n", self.bake_resolution);
+        }
+        text += &format!("_data = [{}]\n", data.join(", "));
+        text += &format!("point_count = {}\n", data.len());
+
+        text
+    }
+}
+
+#[allow(unused)]
+impl Curve {
+    pub fn from_godot_tres(text: &str) -> Result<Self, ParseError> {
+        GodotCurve::from_tres(text).map(|godot| godot.curve)
+    }
+
+    pub fn to_godot_tres(&self) -> String {
+        GodotCurve::new(self.clone()).to_tres()
+    }
+}
+
+fn bracket_depth(text: &str) -> i32 {
+    text.chars().fold(0, |depth, c| match c {
+        '[' | '(' => depth + 1,
+        ']' | ')' => depth - 1,
+        _ => depth,
+    })
+}
+
+/// Splits the top level entries of `[a, b(c, d), e]`.
+fn split_array(text: &str) -> Option<Vec<&str>> {
+    let inner = text.trim().strip_prefix('[')?.strip_suffix(']')?.trim();
+    if inner.is_empty() {
+        return Some(vec![]);
+    }
+
+    let mut elements = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in inner.char_indices() {
+        match c {
+            '[' | '(' => depth += 1,
+            ']' | ')' => depth -= 1,
+            ',' if depth == 0 => {
+                elements.push(inner[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    elements.push(inner[start..].trim());
+
+    Some(elements)
+}
+
+fn parse_real(text: &str, line_number: usize) -> Result<f32, ParseError> {
+    text.trim()
+        .parse()
+        .map_err(|_| ParseError::at_line(line_number, format!("invalid number `{text}`")))
+}
+
+fn parse_vector2(text: &str, line_number: usize) -> Result<Pos2, ParseError> {
+    let error = || ParseError::at_line(line_number, format!("invalid Vector2 `{text}`"));
+
+    let inner = text
+        .strip_prefix("Vector2")
+        .and_then(|rest| rest.trim().strip_prefix('('))
+        .and_then(|rest| rest.strip_suffix(')'))
+        .ok_or_else(error)?;
+    let (x, y) = inner.split_once(',').ok_or_else(error)?;
+
+    Ok(pos2(
+        parse_real(x, line_number)?,
+        parse_real(y, line_number)?,
+    ))
+}
+
+fn parse_mode(text: &str, line_number: usize) -> Result<TangentMode, ParseError> {
+    match text.trim() {
+        "0" => Ok(TangentMode::Free),
+        "1" => Ok(TangentMode::Linear),
+        _ => Err(ParseError::at_line(
+            line_number,
+            format!("invalid tangent mode `{text}`"),
+        )),
+    }
+}
+
+fn mode_to_int(mode: TangentMode) -> u8 {
+    match mode {
+        TangentMode::Free => 0,
+        TangentMode::Linear => 1,
+    }
+}
+
+/// Reals are always written with a decimal point.
+fn format_real(value: f32) -> String {
+    if value.fract() == 0.0 {
+        format!("{:.1}", value + 0.0)
+    } else {
+        format!("{}", value)
+    }
+}
+
+/// Vector components drop the decimal point of whole numbers.
+fn format_number(value: f32) -> String {
+    format!("{}", value + 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GODOT_3: &str = include_str!("../testdata/godot3_curve.tres");
+    const GODOT_4: &str = include_str!("../testdata/godot4_scene.tscn");
+
+    fn assert_point(point: &Point, pos: Pos2, left_tan: f32, right_tan: f32) {
+        assert!(point.pos().distance(pos) < 0.0001, "{:?}", point.pos());
+        assert!((point.left_tan() - left_tan).abs() < 0.0001);
+        assert!((point.right_tan() - right_tan).abs() < 0.0001);
+    }
+
+    fn assert_same_points(a: &Curve, b: &Curve) {
+        assert_eq!(a.points().len(), b.points().len());
+        for (a, b) in a.points().iter().zip(b.points()) {
+            assert_point(a, b.pos(), b.left_tan(), b.right_tan());
+            assert!(a.left_mode() == b.left_mode() && a.right_mode() == b.right_mode());
+        }
+    }
+
+    #[test]
+    fn reads_godot_3_resources() {
+        let godot = GodotCurve::from_tres(GODOT_3).unwrap();
+
+        assert_eq!((godot.min_value, godot.max_value), (-1.0, 2.0));
+        assert_eq!((godot.min_domain, godot.max_domain), (0.0, 1.0));
+        assert_eq!(godot.bake_resolution, 50);
+
+        // Slopes are divided by the value range of 3
+        let points = godot.curve.points();
+        assert_eq!(points.len(), 3);
+        assert_point(&points[0], pos2(0.0, 0.0), 0.0, 0.5);
+        assert_point(&points[1], pos2(0.5, 1.0), 0.0, 0.0);
+        assert_point(&points[2], pos2(1.0, 0.5), -1.0, 0.0);
+        assert!(points[2].right_mode() == TangentMode::Linear);
+    }
+
+    #[test]
+    fn reads_godot_4_sub_resources() {
+        let godot = GodotCurve::from_tres(GODOT_4).unwrap();
+
+        assert_eq!((godot.min_value, godot.max_value), (0.0, 2.0));
+        assert_eq!((godot.min_domain, godot.max_domain), (1.0, 5.0));
+        assert_eq!(godot.bake_resolution, 64);
+
+        // Slopes are scaled by a domain of 4 over a value range of 2
+        let points = godot.curve.points();
+        assert_eq!(points.len(), 3);
+        assert_point(&points[0], pos2(0.0, 0.0), 0.0, 1.0);
+        assert_point(&points[1], pos2(0.5, 1.0), 0.0, 0.0);
+        assert_point(&points[2], pos2(1.0, 0.5), -0.5, 0.0);
+    }
+
+    #[test]
+    fn round_trips_godot_3_resources() {
+        let curve = Curve::from_godot_tres(GODOT_3).unwrap();
+        let text = curve.to_godot_tres();
+
+        assert!(text.starts_with("[gd_resource type=\"Curve\" format=3]"));
+        assert_same_points(&Curve::from_godot_tres(&text).unwrap(), &curve);
+    }
+
+    #[test]
+    fn round_trips_godot_4_ranges() {
+        let godot = GodotCurve::from_tres(GODOT_4).unwrap();
+        let text = godot.to_tres();
+
+        // The original slopes and ranges are written back
+        assert!(text.contains("max_value = 2.0\n"));
+        assert!(text.contains("min_domain = 1.0\n"));
+        assert!(text.contains("max_domain = 5.0\n"));
+        assert!(text.contains("bake_resolution = 64\n"));
+        assert!(!text.contains("min_value"));
+        assert!(text.contains(
+            "_data = [Vector2(1, 0), 0.0, 0.5, 0, 0, Vector2(3, 2), 0.0, 0.0, 0, 0, \
+             Vector2(5, 1), -0.25, 0.0, 0, 0]\n"
+        ));
+        assert!(text.contains("point_count = 3\n"));
+
+        let again = GodotCurve::from_tres(&text).unwrap();
+        assert_eq!(
+            (
+                again.min_value,
+                again.max_value,
+                again.min_domain,
+                again.max_domain
+            ),
+            (
+                godot.min_value,
+                godot.max_value,
+                godot.min_domain,
+                godot.max_domain
+            )
+        );
+        assert_same_points(&again.curve, &godot.curve);
+
+        let curve = Curve::from_godot_tres(GODOT_4).unwrap();
+        assert_same_points(
+            &Curve::from_godot_tres(&curve.to_godot_tres()).unwrap(),
+            &curve,
+        );
+    }
+
+    #[test]
+    fn reads_multi_line_data() {
+        let text = "[gd_resource type=\"Curve\" format=3]\n\n[resource]\n_data = [\n\
+                    Vector2(0, 0), 0.0, 0.0, 0, 1,\nVector2(1, 1), 0.0, 0.0, 1, 0\n]\n";
+        let curve = Curve::from_godot_tres(text).unwrap();
+
+        assert_eq!(curve.points().len(), 2);
+        assert_point(&curve.points()[0], pos2(0.0, 0.0), 0.0, 1.0);
+    }
+
+    #[test]
+    fn rejects_invalid_resources() {
+        assert!(Curve::from_godot_tres("[gd_resource type=\"Gradient\" format=3]\n").is_err());
+        assert!(Curve::from_godot_tres("").is_err());
+
+        let error = Curve::from_godot_tres(
+            "[gd_resource type=\"Curve\" format=3]\n[resource]\n_data = [Vector2(0, 0), 0.0]\n",
+        )
+        .err()
+        .unwrap();
+        assert_eq!(error.line, Some(3));
+
+        let error = Curve::from_godot_tres(
+            "[gd_resource type=\"Curve\" format=3]\n[resource]\nmin_value = 1.0\nmax_value = 1.0\n",
+        )
+        .err()
+        .unwrap();
+        assert_eq!(error.message, "empty value or domain range");
+    }
+
+    #[test]
+    fn writes_empty_curves() {
+        let text = Curve::default().to_godot_tres();
+
+        assert!(text.contains("_data = []\n"));
+        assert_eq!(Curve::from_godot_tres(&text).unwrap().points().len(), 0);
+    }
+}
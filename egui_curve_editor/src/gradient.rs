@@ -0,0 +1,417 @@
+// Colors over `0..=1`, with separate stops for color and alpha so that either can change on its own.
+
+use crate::color::Color32;
+use crate::prelude::*;
+use crate::Sampleable;
+
+/// How a [`Gradient`] blends between its stops.
+#[derive(PartialEq, Clone, Copy, Debug, Default, serde::Deserialize, serde::Serialize)]
+pub enum GradientInterpolation {
+    /// Blends in linear RGB.
+    #[default]
+    Linear,
+    /// Holds every stop until the next one.
+    Constant,
+    /// Blends in Oklab, which keeps lightness even and avoids muddy middles.
+    Oklab,
+}
+
+/// An opaque color at a time, alpha comes from the [`AlphaStop`]s.
+#[derive(PartialEq, Clone, Copy, Debug, serde::Deserialize, serde::Serialize)]
+pub struct ColorStop {
+    pub time: f32,
+    pub color: Color32,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug, serde::Deserialize, serde::Serialize)]
+pub struct AlphaStop {
+    pub time: f32,
+    pub alpha: f32,
+}
+
+/// Color and alpha stops over `0..=1`, held flat before the first stop and after the last one.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct Gradient {
+    color_stops: Vec<ColorStop>,
+    alpha_stops: Vec<AlphaStop>,
+    #[serde(default)]
+    interpolation: GradientInterpolation,
+}
+
+impl Default for Gradient {
+    /// Opaque black to white.
+    fn default() -> Self {
+        Self::new(Color32::BLACK, Color32::WHITE)
+    }
+}
+
+#[allow(unused)]
+impl Gradient {
+    /// Blends from `start` to `end`, alpha included.
+    pub fn new(start: impl Into<Color32>, end: impl Into<Color32>) -> Self {
+        let (start, end) = (start.into(), end.into());
+        let alpha = |color: Color32| color.a() as f32 / 255.0;
+
+        Self {
+            color_stops: vec![
+                ColorStop {
+                    time: 0.0,
+                    color: opaque(start),
+                },
+                ColorStop {
+                    time: 1.0,
+                    color: opaque(end),
+                },
+            ],
+            alpha_stops: vec![
+                AlphaStop {
+                    time: 0.0,
+                    alpha: alpha(start),
+                },
+                AlphaStop {
+                    time: 1.0,
+                    alpha: alpha(end),
+                },
+            ],
+            interpolation: GradientInterpolation::default(),
+        }
+    }
+
+    pub fn with_interpolation(self, interpolation: GradientInterpolation) -> Self {
+        Self {
+            interpolation,
+            ..self
+        }
+    }
+
+    pub fn interpolation(&self) -> GradientInterpolation {
+        self.interpolation
+    }
+
+    pub fn set_interpolation(&mut self, interpolation: GradientInterpolation) {
+        self.interpolation = interpolation;
+    }
+
+    pub fn color_stops(&self) -> &[ColorStop] {
+        &self.color_stops
+    }
+
+    pub fn alpha_stops(&self) -> &[AlphaStop] {
+        &self.alpha_stops
+    }
+
+    /// Returns the index of the new stop, the alpha of `color` is ignored.
+    ///
+    /// Stops at non-finite times are not added and give `None`.
+    pub fn add_color_stop(&mut self, time: f32, color: impl Into<Color32>) -> Option<usize> {
+        if !time.is_finite() {
+            return None;
+        }
+
+        let color = color.into();
+        let time = time.clamp(0.0, 1.0);
+        let index = self.color_stops.partition_point(|stop| stop.time <= time);
+        self.color_stops.insert(
+            index,
+            ColorStop {
+                time,
+                color: opaque(color),
+            },
+        );
+
+        Some(index)
+    }
+
+    /// Returns the index of the new stop, stops at non-finite times give `None`.
+    pub fn add_alpha_stop(&mut self, time: f32, alpha: f32) -> Option<usize> {
+        if !time.is_finite() {
+            return None;
+        }
+
+        let time = time.clamp(0.0, 1.0);
+        let index = self.alpha_stops.partition_point(|stop| stop.time <= time);
+        self.alpha_stops.insert(
+            index,
+            AlphaStop {
+                time,
+                alpha: alpha.clamp(0.0, 1.0),
+            },
+        );
+
+        Some(index)
+    }
+
+    /// Removes a stop unless it is the last color stop left.
+    pub fn remove_color_stop(&mut self, index: usize) -> Option<ColorStop> {
+        (index < self.color_stops.len() && self.color_stops.len() > 1)
+            .then(|| self.color_stops.remove(index))
+    }
+
+    /// Removes a stop unless it is the last alpha stop left.
+    pub fn remove_alpha_stop(&mut self, index: usize) -> Option<AlphaStop> {
+        (index < self.alpha_stops.len() && self.alpha_stops.len() > 1)
+            .then(|| self.alpha_stops.remove(index))
+    }
+
+    /// Moves a stop, which may pass others. Returns its new index.
+    ///
+    /// Non-finite times leave the stop where it is.
+    pub fn set_color_stop_time(&mut self, index: usize, time: f32) -> usize {
+        match self.color_stops.get(index) {
+            Some(stop) if time.is_finite() => {
+                let color = stop.color;
+                self.color_stops.remove(index);
+                self.add_color_stop(time, color).unwrap_or(index)
+            }
+            _ => index,
+        }
+    }
+
+    /// Moves a stop, which may pass others. Returns its new index.
+    ///
+    /// Non-finite times leave the stop where it is.
+    pub fn set_alpha_stop_time(&mut self, index: usize, time: f32) -> usize {
+        match self.alpha_stops.get(index) {
+            Some(stop) if time.is_finite() => {
+                let alpha = stop.alpha;
+                self.alpha_stops.remove(index);
+                self.add_alpha_stop(time, alpha).unwrap_or(index)
+            }
+            _ => index,
+        }
+    }
+
+    pub fn set_color(&mut self, index: usize, color: impl Into<Color32>) {
+        if let Some(stop) = self.color_stops.get_mut(index) {
+            stop.color = opaque(color.into());
+        }
+    }
+
+    pub fn set_alpha(&mut self, index: usize, alpha: f32) {
+        if let Some(stop) = self.alpha_stops.get_mut(index) {
+            stop.alpha = alpha.clamp(0.0, 1.0);
+        }
+    }
+
+    pub fn sample(&self, t: f32) -> Color32 {
+        let [r, g, b] = self.sample_color(t);
+        let alpha = self.sample_alpha(t);
+
+        Color32::from_rgba_unmultiplied(
+            crate::color::gamma_u8_from_linear_f32(r),
+            crate::color::gamma_u8_from_linear_f32(g),
+            crate::color::gamma_u8_from_linear_f32(b),
+            crate::color::linear_u8_from_linear_f32(alpha),
+        )
+    }
+
+    /// Linear RGB at `t`.
+    fn sample_color(&self, t: f32) -> [f32; 3] {
+        let Some((a, b, s)) = segment(&self.color_stops, |stop| stop.time, t) else {
+            return [1.0; 3];
+        };
+        let (a, b) = (linear_rgb(a.color), linear_rgb(b.color));
+
+        match self.interpolation {
+            GradientInterpolation::Constant => a,
+            GradientInterpolation::Linear => lerp3(a, b, s),
+            GradientInterpolation::Oklab => {
+                oklab_to_linear(lerp3(linear_to_oklab(a), linear_to_oklab(b), s))
+            }
+        }
+    }
+
+    fn sample_alpha(&self, t: f32) -> f32 {
+        let Some((a, b, s)) = segment(&self.alpha_stops, |stop| stop.time, t) else {
+            return 1.0;
+        };
+
+        match self.interpolation {
+            GradientInterpolation::Constant => a.alpha,
+            GradientInterpolation::Linear | GradientInterpolation::Oklab => {
+                a.alpha + (b.alpha - a.alpha) * s
+            }
+        }
+    }
+}
+
+impl Sampleable<Color32> for Gradient {
+    fn sample(&self, t: f32) -> Color32 {
+        Gradient::sample(self, t)
+    }
+}
+
+/// The stops around `t` and how far `t` is between them, the first stop for a non-finite `t`.
+fn segment<S>(stops: &[S], time: impl Fn(&S) -> f32, t: f32) -> Option<(&S, &S, f32)> {
+    let (first, last) = (stops.first()?, stops.last()?);
+    if !t.is_finite() || t <= time(first) {
+        return Some((first, first, 0.0));
+    }
+    if t >= time(last) {
+        return Some((last, last, 0.0));
+    }
+
+    let index = stops
+        .partition_point(|stop| time(stop) <= t)
+        .saturating_sub(1);
+    let (a, b) = (&stops[index], &stops[index + 1]);
+    let d = time(b) - time(a);
+    let s = if d > 0.0 { (t - time(a)) / d } else { 0.0 };
+
+    Some((a, b, s))
+}
+
+fn opaque(color: Color32) -> Color32 {
+    Color32::from_rgb(color.r(), color.g(), color.b())
+}
+
+fn linear_rgb(color: Color32) -> [f32; 3] {
+    [color.r(), color.g(), color.b()].map(crate::color::linear_f32_from_gamma_u8)
+}
+
+fn lerp3(a: [f32; 3], b: [f32; 3], t: f32) -> [f32; 3] {
+    [0, 1, 2].map(|i| a[i] + (b[i] - a[i]) * t)
+}
+
+// From https://bottosson.github.io/posts/oklab/, constants kept as published
+
+#[allow(clippy::excessive_precision)]
+fn linear_to_oklab([r, g, b]: [f32; 3]) -> [f32; 3] {
+    let l = (0.4122214708 * r + 0.5363325363 * g + 0.0514459929 * b).cbrt();
+    let m = (0.2119034982 * r + 0.6806995451 * g + 0.1073969566 * b).cbrt();
+    let s = (0.0883024619 * r + 0.2817188376 * g + 0.6299787005 * b).cbrt();
+
+    [
+        0.2104542553 * l + 0.7936177850 * m - 0.0040720468 * s,
+        1.9779984951 * l - 2.4285922050 * m + 0.4505937099 * s,
+        0.0259040371 * l + 0.7827717662 * m - 0.8086757660 * s,
+    ]
+}
+
+#[allow(clippy::excessive_precision)]
+fn oklab_to_linear([l, a, b]: [f32; 3]) -> [f32; 3] {
+    let l_ = l + 0.3963377774 * a + 0.2158037573 * b;
+    let m_ = l - 0.1055613458 * a - 0.0638541728 * b;
+    let s_ = l - 0.0894841775 * a - 1.2914855480 * b;
+    let (l, m, s) = (l_ * l_ * l_, m_ * m_ * m_, s_ * s_ * s_);
+
+    [
+        4.0767416621 * l - 3.3077115913 * m + 0.2309699292 * s,
+        -1.2684380046 * l + 2.6097574011 * m - 0.3413193965 * s,
+        -0.0041960863 * l - 0.7034186147 * m + 1.7076147010 * s,
+    ]
+    .map(|value| value.clamp(0.0, 1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blends_linearly_and_holds_constant() {
+        let mut gradient = Gradient::default();
+        assert_eq!(gradient.sample(0.0), Color32::BLACK);
+        assert_eq!(gradient.sample(1.0), Color32::WHITE);
+        assert_eq!(gradient.sample(-1.0), Color32::BLACK);
+        assert_eq!(gradient.sample(2.0), Color32::WHITE);
+
+        // Halfway in linear RGB is brighter than halfway in gamma
+        let middle = gradient.sample(0.5);
+        assert_eq!(middle.r(), crate::color::gamma_u8_from_linear_f32(0.5));
+        assert_eq!((middle.r(), middle.a()), (middle.b(), 255));
+
+        gradient.set_interpolation(GradientInterpolation::Constant);
+        assert_eq!(gradient.sample(0.5), Color32::BLACK);
+        assert_eq!(gradient.sample(0.99), Color32::BLACK);
+        assert_eq!(gradient.sample(1.0), Color32::WHITE);
+    }
+
+    #[test]
+    fn mixes_in_oklab() {
+        let gradient = Gradient::default().with_interpolation(GradientInterpolation::Oklab);
+        assert_eq!(gradient.sample(0.0), Color32::BLACK);
+        assert_eq!(gradient.sample(1.0), Color32::WHITE);
+
+        // Half lightness is a quarter of linear white
+        let middle = gradient.sample(0.5);
+        let expected = crate::color::gamma_u8_from_linear_f32(0.125);
+        for channel in [middle.r(), middle.g(), middle.b()] {
+            assert!(channel.abs_diff(expected) <= 1, "{middle:?}");
+        }
+    }
+
+    #[test]
+    fn keeps_alpha_apart_from_color() {
+        let mut gradient =
+            Gradient::new(Color32::from_rgba_unmultiplied(255, 0, 0, 0), Color32::RED);
+        assert_eq!(gradient.color_stops()[0].color, Color32::RED);
+        assert_eq!(gradient.alpha_stops()[0].alpha, 0.0);
+        assert_eq!(
+            gradient.sample(0.0),
+            Color32::from_rgba_unmultiplied(255, 0, 0, 0)
+        );
+
+        // A color stop changes the color but not the alpha
+        assert_eq!(gradient.add_color_stop(0.5, Color32::BLUE), Some(1));
+        let middle = gradient.sample(0.5);
+        assert_eq!(middle.to_srgba_unmultiplied(), [0, 0, 255, 128]);
+
+        // An alpha stop changes the alpha but not the color
+        assert_eq!(gradient.add_alpha_stop(0.5, 1.0), Some(1));
+        assert_eq!(gradient.sample(0.5), Color32::BLUE);
+        assert_eq!(gradient.color_stops().len(), 3);
+    }
+
+    #[test]
+    fn keeps_stops_ordered_after_moving() {
+        let mut gradient = Gradient::default();
+        gradient.add_color_stop(0.5, Color32::RED);
+        let times = |gradient: &Gradient| {
+            gradient
+                .color_stops()
+                .iter()
+                .map(|stop| stop.time)
+                .collect::<Vec<_>>()
+        };
+
+        // The black stop passes the others
+        assert_eq!(gradient.set_color_stop_time(0, 0.75), 1);
+        assert_eq!(times(&gradient), [0.5, 0.75, 1.0]);
+        assert_eq!(gradient.color_stops()[1].color, Color32::BLACK);
+
+        assert_eq!(gradient.set_color_stop_time(2, -1.0), 0);
+        assert_eq!(times(&gradient), [0.0, 0.5, 0.75]);
+        assert_eq!(gradient.color_stops()[0].color, Color32::WHITE);
+
+        assert_eq!(gradient.set_alpha_stop_time(1, 0.25), 1);
+        assert_eq!(gradient.set_alpha_stop_time(1, f32::NAN), 1);
+        assert_eq!(gradient.alpha_stops()[1].time, 0.25);
+    }
+
+    #[test]
+    fn keeps_the_last_stop() {
+        let mut gradient = Gradient::default();
+        assert!(gradient.remove_color_stop(2).is_none());
+        assert!(gradient.remove_color_stop(0).is_some());
+        assert!(gradient.remove_color_stop(0).is_none());
+        assert!(gradient.remove_alpha_stop(1).is_some());
+        assert!(gradient.remove_alpha_stop(0).is_none());
+
+        assert_eq!(gradient.color_stops().len(), 1);
+        assert_eq!(gradient.alpha_stops().len(), 1);
+        assert_eq!(gradient.sample(0.0), Color32::WHITE);
+        assert_eq!(gradient.sample(1.0), Color32::WHITE);
+    }
+
+    #[test]
+    fn handles_non_finite_times() {
+        let mut gradient = Gradient::default();
+        assert_eq!(gradient.sample(f32::NAN), Color32::BLACK);
+        assert_eq!(gradient.sample(f32::INFINITY), Color32::BLACK);
+
+        assert_eq!(gradient.add_color_stop(f32::NAN, Color32::RED), None);
+        assert_eq!(gradient.add_alpha_stop(f32::NEG_INFINITY, 0.5), None);
+        assert_eq!(gradient.color_stops().len(), 2);
+        assert_eq!(gradient.alpha_stops().len(), 2);
+    }
+}
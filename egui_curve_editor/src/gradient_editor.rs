@@ -0,0 +1,360 @@
+use egui::NumExt;
+
+use crate::{Gradient, GradientInterpolation};
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Copy, PartialEq, Debug)]
+enum StopKind {
+    Color,
+    Alpha,
+}
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Default)]
+struct GradientEditorState {
+    dragging: bool,
+    selected: Option<(StopKind, usize)>,
+}
+
+/// Edits a [`Gradient`], with alpha stops above the bar and color stops below it.
+///
+/// Clicking adds a stop, to the alpha stops above the middle of the bar and to the color stops
+/// below it. Stops are dragged in time and removed with a right click, the selected one is edited
+/// under the bar.
+pub struct GradientEditor<'a> {
+    gradient: &'a mut Gradient,
+    width: Option<f32>,
+    bar_height: f32,
+}
+
+#[allow(unused)]
+impl<'a> GradientEditor<'a> {
+    const MARKER_SIZE: f32 = 10.0;
+
+    pub fn new(gradient: &'a mut Gradient) -> Self {
+        Self {
+            gradient,
+            width: None,
+            bar_height: 24.0,
+        }
+    }
+
+    pub fn with_width(self, width: f32) -> Self {
+        Self {
+            width: Some(width),
+            ..self
+        }
+    }
+
+    pub fn with_bar_height(self, bar_height: f32) -> Self {
+        Self { bar_height, ..self }
+    }
+
+    fn load_state(ctx: &egui::Context, id: egui::Id) -> Option<GradientEditorState> {
+        ctx.data_mut(|d| d.get_persisted(id))
+    }
+
+    fn store_state(ctx: &egui::Context, id: egui::Id, state: GradientEditorState) {
+        ctx.data_mut(|d| d.insert_persisted(id, state));
+    }
+
+    /// Marker positions, alpha stops pointing down at the bar and color stops pointing up.
+    fn markers(&self, bar_rect: egui::Rect) -> Vec<(StopKind, usize, egui::Pos2)> {
+        let x = |time: f32| bar_rect.left() + time * bar_rect.width();
+        let half = Self::MARKER_SIZE * 0.5;
+
+        let alpha = self
+            .gradient
+            .alpha_stops()
+            .iter()
+            .enumerate()
+            .map(|(index, stop)| {
+                let pos = egui::pos2(x(stop.time), bar_rect.top() - half);
+                (StopKind::Alpha, index, pos)
+            });
+        let color = self
+            .gradient
+            .color_stops()
+            .iter()
+            .enumerate()
+            .map(|(index, stop)| {
+                let pos = egui::pos2(x(stop.time), bar_rect.bottom() + half);
+                (StopKind::Color, index, pos)
+            });
+
+        alpha.chain(color).collect()
+    }
+
+    fn paint_bar(&self, painter: &egui::Painter, bar_rect: egui::Rect) {
+        // Checkers behind, so that alpha shows
+        let checker = (bar_rect.height() / 2.0).at_least(1.0);
+        let columns = (bar_rect.width() / checker).ceil() as usize;
+        for column in 0..columns {
+            for row in 0..2 {
+                let fill = if (column + row) % 2 == 0 {
+                    egui::Color32::from_gray(100)
+                } else {
+                    egui::Color32::from_gray(160)
+                };
+                let min = bar_rect.min + egui::vec2(column as f32, row as f32) * checker;
+                let rect =
+                    egui::Rect::from_min_size(min, egui::Vec2::splat(checker)).intersect(bar_rect);
+                painter.rect_filled(rect, 0, fill);
+            }
+        }
+
+        // One column of the mesh per pixel
+        let resolution = bar_rect.width().ceil().at_least(1.0) as usize;
+        let mut mesh = egui::Mesh::default();
+        for i in 0..=resolution {
+            let t = i as f32 / resolution as f32;
+            let x = bar_rect.left() + t * bar_rect.width();
+            let color = self.gradient.sample(t).into();
+
+            mesh.colored_vertex(egui::pos2(x, bar_rect.top()), color);
+            mesh.colored_vertex(egui::pos2(x, bar_rect.bottom()), color);
+            if i > 0 {
+                let base = 2 * i as u32;
+                mesh.add_triangle(base - 2, base - 1, base);
+                mesh.add_triangle(base - 1, base, base + 1);
+            }
+        }
+        painter.add(mesh);
+
+        painter.rect_stroke(
+            bar_rect,
+            0,
+            egui::Stroke::new(1.0, egui::Color32::from_gray(60)),
+            egui::StrokeKind::Outside,
+        );
+    }
+
+    fn paint_marker(
+        &self,
+        painter: &egui::Painter,
+        kind: StopKind,
+        index: usize,
+        pos: egui::Pos2,
+        stroke: egui::Stroke,
+    ) {
+        let half = Self::MARKER_SIZE * 0.5;
+        let (fill, tip) = match kind {
+            StopKind::Alpha => {
+                let alpha = self.gradient.alpha_stops()[index].alpha;
+                (
+                    egui::Color32::from_gray((alpha * 255.0).round() as u8),
+                    egui::vec2(0.0, half),
+                )
+            }
+            StopKind::Color => (
+                self.gradient.color_stops()[index].color.into(),
+                egui::vec2(0.0, -half),
+            ),
+        };
+
+        painter.add(egui::Shape::convex_polygon(
+            vec![
+                pos + tip,
+                pos - tip + egui::vec2(half, 0.0),
+                pos - tip - egui::vec2(half, 0.0),
+            ],
+            fill,
+            stroke,
+        ));
+    }
+}
+
+impl egui::Widget for GradientEditor<'_> {
+    fn ui(self, ui: &mut egui::Ui) -> egui::Response {
+        let width = self
+            .width
+            .unwrap_or_else(|| ui.available_size_before_wrap().x)
+            .at_least(Self::MARKER_SIZE * 2.0);
+        let size = egui::vec2(width, self.bar_height + Self::MARKER_SIZE * 2.0);
+
+        let id = ui.next_auto_id();
+        let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click_and_drag());
+        ui.ctx().check_for_id_clash(id, rect, "Gradient");
+
+        // Markers overhang the ends of the bar by half their width
+        let bar_rect = egui::Rect::from_min_max(
+            rect.min + egui::vec2(Self::MARKER_SIZE * 0.5, Self::MARKER_SIZE),
+            rect.max - egui::vec2(Self::MARKER_SIZE * 0.5, Self::MARKER_SIZE),
+        );
+        let time_at = |x: f32| ((x - bar_rect.left()) / bar_rect.width()).clamp(0.0, 1.0);
+
+        let mut state = Self::load_state(ui.ctx(), id).unwrap_or_default();
+
+        // Drop a selection the gradient no longer has
+        let exists = |gradient: &Gradient, selected: Option<(StopKind, usize)>| match selected {
+            Some((StopKind::Color, index)) => index < gradient.color_stops().len(),
+            Some((StopKind::Alpha, index)) => index < gradient.alpha_stops().len(),
+            None => true,
+        };
+        if !exists(self.gradient, state.selected) {
+            state.selected = None;
+            state.dragging = false;
+        }
+
+        if (response.clicked() || response.secondary_clicked() || response.dragged())
+            && response.hover_pos().is_some()
+            && !state.dragging
+        {
+            let pos = response.hover_pos().unwrap();
+
+            let near = self
+                .markers(bar_rect)
+                .into_iter()
+                .filter(|(_, _, marker)| marker.distance(pos) < Self::MARKER_SIZE)
+                .min_by(|a, b| a.2.distance(pos).total_cmp(&b.2.distance(pos)));
+
+            if let Some((kind, index, _)) = near {
+                // Start dragging?
+                if response.clicked_by(egui::PointerButton::Primary)
+                    || response.dragged_by(egui::PointerButton::Primary)
+                {
+                    state.dragging = true;
+                    state.selected = Some((kind, index));
+                }
+
+                // Remove stop?
+                if response.secondary_clicked() {
+                    match kind {
+                        StopKind::Color => self.gradient.remove_color_stop(index).map(|_| ()),
+                        StopKind::Alpha => self.gradient.remove_alpha_stop(index).map(|_| ()),
+                    };
+                    state.dragging = false;
+                    state.selected = None;
+                }
+            } else if response.clicked_by(egui::PointerButton::Primary)
+                || response.dragged_by(egui::PointerButton::Primary)
+            {
+                // Add stop, with the current value of the gradient
+                let time = time_at(pos.x);
+                let color = self.gradient.sample(time);
+                let added = if pos.y < bar_rect.center().y {
+                    let alpha = color.a() as f32 / 255.0;
+                    self.gradient
+                        .add_alpha_stop(time, alpha)
+                        .map(|index| (StopKind::Alpha, index))
+                } else {
+                    self.gradient
+                        .add_color_stop(time, color)
+                        .map(|index| (StopKind::Color, index))
+                };
+
+                // A drag moves the new stop, rather than adding one every frame
+                if added.is_some() {
+                    state.selected = added;
+                    state.dragging = response.dragged_by(egui::PointerButton::Primary);
+                }
+            }
+        }
+
+        // Stop dragging?
+        if state.dragging && (response.drag_stopped() || !response.is_pointer_button_down_on()) {
+            state.dragging = false;
+        }
+
+        // Handle dragging
+        if let (Some((kind, index)), true) = (state.selected, state.dragging) {
+            let delta = response.drag_delta().x / bar_rect.width();
+            state.selected = Some(match kind {
+                StopKind::Color => {
+                    let time = self.gradient.color_stops()[index].time + delta;
+                    (kind, self.gradient.set_color_stop_time(index, time))
+                }
+                StopKind::Alpha => {
+                    let time = self.gradient.alpha_stops()[index].time + delta;
+                    (kind, self.gradient.set_alpha_stop_time(index, time))
+                }
+            });
+        }
+
+        let painter = ui.painter().with_clip_rect(rect.expand(1.0));
+        self.paint_bar(&painter, bar_rect);
+
+        let visuals = ui.style().interact(&response);
+        for (kind, index, pos) in self.markers(bar_rect) {
+            let stroke = if state.selected == Some((kind, index)) {
+                egui::Stroke::new(2.0, ui.visuals().selection.bg_fill)
+            } else {
+                visuals.fg_stroke
+            };
+            self.paint_marker(&painter, kind, index, pos, stroke);
+        }
+
+        // Selected stop and interpolation
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            match state.selected {
+                Some((StopKind::Color, index)) => {
+                    let mut stop = self.gradient.color_stops()[index];
+                    let mut color = stop.color.into();
+                    if ui.color_edit_button_srgba(&mut color).changed() {
+                        self.gradient.set_color(index, color);
+                        changed = true;
+                    }
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut stop.time)
+                                .speed(0.005)
+                                .range(0.0..=1.0),
+                        )
+                        .changed()
+                    {
+                        let index = self.gradient.set_color_stop_time(index, stop.time);
+                        state.selected = Some((StopKind::Color, index));
+                        changed = true;
+                    }
+                }
+                Some((StopKind::Alpha, index)) => {
+                    let mut stop = self.gradient.alpha_stops()[index];
+                    if ui
+                        .add(egui::Slider::new(&mut stop.alpha, 0.0..=1.0).text("alpha"))
+                        .changed()
+                    {
+                        self.gradient.set_alpha(index, stop.alpha);
+                        changed = true;
+                    }
+                    if ui
+                        .add(
+                            egui::DragValue::new(&mut stop.time)
+                                .speed(0.005)
+                                .range(0.0..=1.0),
+                        )
+                        .changed()
+                    {
+                        let index = self.gradient.set_alpha_stop_time(index, stop.time);
+                        state.selected = Some((StopKind::Alpha, index));
+                        changed = true;
+                    }
+                }
+                None => {}
+            }
+
+            let mut interpolation = self.gradient.interpolation();
+            egui::ComboBox::from_id_salt(id.with("interpolation"))
+                .selected_text(format!("{interpolation:?}"))
+                .show_ui(ui, |ui| {
+                    for option in [
+                        GradientInterpolation::Linear,
+                        GradientInterpolation::Constant,
+                        GradientInterpolation::Oklab,
+                    ] {
+                        changed |= ui
+                            .selectable_value(&mut interpolation, option, format!("{option:?}"))
+                            .changed();
+                    }
+                });
+            self.gradient.set_interpolation(interpolation);
+        });
+
+        Self::store_state(ui.ctx(), id, state);
+
+        let mut response = response;
+        if changed {
+            response.mark_changed();
+        }
+
+        response
+    }
+}
@@ -0,0 +1,670 @@
+// Curves over values made of several channels, like vectors, colors and rotations.
+//
+// Every channel is a cubic segment between keys shaped like the ones of `Curve`, with its own
+// tangents. Keys are shared, so all channels change at the same times.
+
+use crate::color::Color32;
+use crate::curve::bezier_interpolate;
+use crate::math::{Rangef, Vec2};
+use crate::prelude::*;
+use crate::{Sampleable, TangentMode};
+
+/// A value a [`KeyedCurve`] can interpolate, seen as a fixed number of channels.
+pub trait CurveValue: Copy {
+    const CHANNELS: usize;
+
+    fn channel(&self, channel: usize) -> f64;
+
+    fn set_channel(&mut self, channel: usize, value: f64);
+
+    fn zero() -> Self;
+
+    /// Corrects a value interpolated channel by channel between `a` and `b`.
+    fn finish(a: Self, b: Self, t: f32, interpolated: Self) -> Self {
+        let _ = (a, b, t);
+        interpolated
+    }
+
+    /// Color the channel is drawn with in the editor.
+    fn channel_color(channel: usize) -> Color32 {
+        const COLORS: [Color32; 4] = [
+            Color32::from_rgb(230, 80, 80),
+            Color32::from_rgb(90, 200, 90),
+            Color32::from_rgb(90, 130, 240),
+            Color32::from_rgb(220, 200, 80),
+        ];
+
+        COLORS[channel % COLORS.len()]
+    }
+
+    /// Values shown by the editor unless it is given a range.
+    fn default_range() -> Rangef {
+        Rangef::new(0.0, 1.0)
+    }
+}
+
+impl CurveValue for f32 {
+    const CHANNELS: usize = 1;
+
+    fn channel(&self, _channel: usize) -> f64 {
+        *self as f64
+    }
+
+    fn set_channel(&mut self, _channel: usize, value: f64) {
+        *self = value as f32;
+    }
+
+    fn zero() -> Self {
+        0.0
+    }
+}
+
+impl CurveValue for f64 {
+    const CHANNELS: usize = 1;
+
+    fn channel(&self, _channel: usize) -> f64 {
+        *self
+    }
+
+    fn set_channel(&mut self, _channel: usize, value: f64) {
+        *self = value;
+    }
+
+    fn zero() -> Self {
+        0.0
+    }
+}
+
+impl CurveValue for Vec2 {
+    const CHANNELS: usize = 2;
+
+    fn channel(&self, channel: usize) -> f64 {
+        self[channel] as f64
+    }
+
+    fn set_channel(&mut self, channel: usize, value: f64) {
+        self[channel] = value as f32;
+    }
+
+    fn zero() -> Self {
+        Vec2::ZERO
+    }
+}
+
+#[cfg(feature = "egui")]
+impl CurveValue for egui::Vec2 {
+    const CHANNELS: usize = 2;
+
+    fn channel(&self, channel: usize) -> f64 {
+        self[channel] as f64
+    }
+
+    fn set_channel(&mut self, channel: usize, value: f64) {
+        self[channel] = value as f32;
+    }
+
+    fn zero() -> Self {
+        egui::Vec2::ZERO
+    }
+}
+
+/// Vectors of any size, `[f32; 3]` standing in for a 3D vector.
+impl<const N: usize> CurveValue for [f32; N] {
+    const CHANNELS: usize = N;
+
+    fn channel(&self, channel: usize) -> f64 {
+        self[channel] as f64
+    }
+
+    fn set_channel(&mut self, channel: usize, value: f64) {
+        self[channel] = value as f32;
+    }
+
+    fn zero() -> Self {
+        [0.0; N]
+    }
+}
+
+/// Linear premultiplied channels, in the order red, green, blue, alpha.
+#[cfg(feature = "egui")]
+impl CurveValue for egui::Rgba {
+    const CHANNELS: usize = 4;
+
+    fn channel(&self, channel: usize) -> f64 {
+        self.to_array()[channel] as f64
+    }
+
+    fn set_channel(&mut self, channel: usize, value: f64) {
+        let mut channels = self.to_array();
+        channels[channel] = value as f32;
+        let [r, g, b, a] = channels;
+        *self = egui::Rgba::from_rgba_premultiplied(r, g, b, a);
+    }
+
+    fn zero() -> Self {
+        egui::Rgba::TRANSPARENT
+    }
+
+    fn channel_color(channel: usize) -> Color32 {
+        [
+            Color32::from_rgb(230, 80, 80),
+            Color32::from_rgb(90, 200, 90),
+            Color32::from_rgb(90, 130, 240),
+            Color32::GRAY,
+        ][channel]
+    }
+}
+
+/// A rotation as a unit quaternion.
+#[derive(PartialEq, Clone, Copy, Debug, serde::Deserialize, serde::Serialize)]
+pub struct Quaternion {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+#[allow(unused)]
+impl Quaternion {
+    pub const IDENTITY: Self = Self {
+        x: 0.0,
+        y: 0.0,
+        z: 0.0,
+        w: 1.0,
+    };
+
+    /// Rotation by `angle` radians around `axis`, which does not need to be normalized.
+    pub fn from_axis_angle(axis: [f32; 3], angle: f32) -> Self {
+        let length = (axis[0] * axis[0] + axis[1] * axis[1] + axis[2] * axis[2]).sqrt();
+        if length == 0.0 {
+            return Self::IDENTITY;
+        }
+
+        let (sin, cos) = (angle * 0.5).sin_cos();
+        let scale = sin / length;
+        Self {
+            x: axis[0] * scale,
+            y: axis[1] * scale,
+            z: axis[2] * scale,
+            w: cos,
+        }
+    }
+
+    pub fn dot(self, other: Self) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z + self.w * other.w
+    }
+
+    pub fn normalized(self) -> Self {
+        let length = self.dot(self).sqrt();
+        if length == 0.0 {
+            return Self::IDENTITY;
+        }
+
+        self.scale(1.0 / length)
+    }
+
+    /// Spherical interpolation along the shortest arc.
+    pub fn slerp(self, other: Self, t: f32) -> Self {
+        const EPSILON: f32 = 0.0001;
+
+        let mut cos = self.dot(other);
+        let mut other = other;
+        if cos < 0.0 {
+            cos = -cos;
+            other = other.scale(-1.0);
+        }
+
+        // Nearly equal rotations interpolate linearly
+        if cos > 1.0 - EPSILON {
+            return self.scale(1.0 - t).add(other.scale(t)).normalized();
+        }
+
+        let angle = cos.acos();
+        let sin = angle.sin();
+        self.scale(((1.0 - t) * angle).sin() / sin)
+            .add(other.scale((t * angle).sin() / sin))
+    }
+
+    fn scale(self, factor: f32) -> Self {
+        Self {
+            x: self.x * factor,
+            y: self.y * factor,
+            z: self.z * factor,
+            w: self.w * factor,
+        }
+    }
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            x: self.x + other.x,
+            y: self.y + other.y,
+            z: self.z + other.z,
+            w: self.w + other.w,
+        }
+    }
+}
+
+impl CurveValue for Quaternion {
+    const CHANNELS: usize = 4;
+
+    fn channel(&self, channel: usize) -> f64 {
+        [self.x, self.y, self.z, self.w][channel] as f64
+    }
+
+    fn set_channel(&mut self, channel: usize, value: f64) {
+        let value = value as f32;
+        match channel {
+            0 => self.x = value,
+            1 => self.y = value,
+            2 => self.z = value,
+            _ => self.w = value,
+        }
+    }
+
+    fn zero() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            w: 0.0,
+        }
+    }
+
+    /// Slerps between the keys, tangents only adding how far the channels stray from a lerp.
+    fn finish(a: Self, b: Self, t: f32, interpolated: Self) -> Self {
+        let lerp = a.scale(1.0 - t).add(b.scale(t));
+        let ease = interpolated.add(lerp.scale(-1.0));
+
+        a.slerp(b, t).add(ease).normalized()
+    }
+
+    fn channel_color(channel: usize) -> Color32 {
+        [
+            Color32::from_rgb(230, 80, 80),
+            Color32::from_rgb(90, 200, 90),
+            Color32::from_rgb(90, 130, 240),
+            Color32::GRAY,
+        ][channel]
+    }
+
+    fn default_range() -> Rangef {
+        Rangef::new(-1.0, 1.0)
+    }
+}
+
+/// A value of a [`KeyedCurve`] at a time, with the slope of every channel on both sides.
+#[derive(Clone, Copy, serde::Deserialize, serde::Serialize)]
+pub struct Key<T> {
+    time: f32,
+    value: T,
+    left_tan: T,
+    right_tan: T,
+    left_mode: TangentMode,
+    right_mode: TangentMode,
+}
+
+#[allow(unused)]
+impl<T: CurveValue> Key<T> {
+    /// A key with linear tangents.
+    pub fn new(time: f32, value: T) -> Self {
+        Self {
+            time,
+            value,
+            left_tan: T::zero(),
+            right_tan: T::zero(),
+            left_mode: TangentMode::Linear,
+            right_mode: TangentMode::Linear,
+        }
+    }
+
+    /// A key with free tangents.
+    pub fn with_tangents(time: f32, value: T, left_tan: T, right_tan: T) -> Self {
+        Self {
+            time,
+            value,
+            left_tan,
+            right_tan,
+            left_mode: TangentMode::Free,
+            right_mode: TangentMode::Free,
+        }
+    }
+
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    pub fn value(&self) -> T {
+        self.value
+    }
+
+    pub fn left_tan(&self) -> T {
+        self.left_tan
+    }
+
+    pub fn right_tan(&self) -> T {
+        self.right_tan
+    }
+
+    pub fn left_mode(&self) -> TangentMode {
+        self.left_mode
+    }
+
+    pub fn right_mode(&self) -> TangentMode {
+        self.right_mode
+    }
+}
+
+/// Keys over `0..=1` holding values of any [`CurveValue`].
+///
+/// Unlike [`crate::Curve`], values are not clamped.
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+pub struct KeyedCurve<T> {
+    keys: Vec<Key<T>>,
+}
+
+impl<T> Default for KeyedCurve<T> {
+    fn default() -> Self {
+        Self { keys: vec![] }
+    }
+}
+
+#[allow(unused)]
+impl<T: CurveValue> KeyedCurve<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds a curve from keys in any order, linear tangents are recomputed.
+    pub fn from_keys(keys: impl IntoIterator<Item = Key<T>>) -> Self {
+        let mut keys: Vec<Key<T>> = keys
+            .into_iter()
+            .filter(|key| key.time.is_finite())
+            .map(|mut key| {
+                key.time = key.time.clamp(0.0, 1.0);
+                key
+            })
+            .collect();
+        keys.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+        let mut curve = Self { keys };
+        for index in 0..curve.keys.len() {
+            curve.update_auto_tangents(index);
+        }
+
+        curve
+    }
+
+    pub fn keys(&self) -> &[Key<T>] {
+        &self.keys
+    }
+
+    /// Returns the index of the new key, keys at the same time as others go after them.
+    ///
+    /// Keys at non-finite times are not added and give `None`.
+    pub fn add_key(&mut self, mut key: Key<T>) -> Option<usize> {
+        if !key.time.is_finite() {
+            return None;
+        }
+        key.time = key.time.clamp(0.0, 1.0);
+
+        let index = self.keys.partition_point(|other| other.time <= key.time);
+        self.keys.insert(index, key);
+        self.update_auto_tangents(index);
+
+        Some(index)
+    }
+
+    pub fn remove_key(&mut self, index: usize) -> Option<Key<T>> {
+        if index >= self.keys.len() {
+            return None;
+        }
+
+        let key = self.keys.remove(index);
+        if index > 0 {
+            self.update_auto_tangents(index - 1);
+        }
+        if index < self.keys.len() {
+            self.update_auto_tangents(index);
+        }
+
+        Some(key)
+    }
+
+    /// Moves a key in time, between its neighbors.
+    pub fn set_time(&mut self, index: usize, time: f32) {
+        if index >= self.keys.len() || !time.is_finite() {
+            return;
+        }
+
+        let min = index.checked_sub(1).map_or(0.0, |i| self.keys[i].time);
+        let max = self.keys.get(index + 1).map_or(1.0, |key| key.time);
+        self.keys[index].time = time.clamp(min, max);
+        self.update_auto_tangents(index);
+    }
+
+    pub fn set_value(&mut self, index: usize, value: T) {
+        if let Some(key) = self.keys.get_mut(index) {
+            key.value = value;
+            self.update_auto_tangents(index);
+        }
+    }
+
+    pub fn set_channel(&mut self, index: usize, channel: usize, value: f64) {
+        if let Some(key) = self.keys.get_mut(index) {
+            key.value.set_channel(channel, value);
+            self.update_auto_tangents(index);
+        }
+    }
+
+    /// Sets the left slope of one channel, which makes the left side free.
+    pub fn set_left_tan(&mut self, index: usize, channel: usize, tangent: f64) {
+        if let Some(key) = self.keys.get_mut(index) {
+            key.left_tan.set_channel(channel, tangent);
+            key.left_mode = TangentMode::Free;
+        }
+    }
+
+    /// Sets the right slope of one channel, which makes the right side free.
+    pub fn set_right_tan(&mut self, index: usize, channel: usize, tangent: f64) {
+        if let Some(key) = self.keys.get_mut(index) {
+            key.right_tan.set_channel(channel, tangent);
+            key.right_mode = TangentMode::Free;
+        }
+    }
+
+    /// Value at `x`, held flat before the first key and after the last one.
+    ///
+    /// A NaN `x` gives the value of the first key.
+    pub fn sample(&self, x: f32) -> T {
+        const EPSILON: f32 = 0.00001;
+
+        let (Some(first), Some(last)) = (self.keys.first(), self.keys.last()) else {
+            return T::zero();
+        };
+        if x.is_nan() || x <= first.time {
+            return first.value;
+        }
+        if x >= last.time {
+            return last.value;
+        }
+
+        let index = self
+            .keys
+            .partition_point(|key| key.time <= x)
+            .saturating_sub(1);
+        let (a, b) = (&self.keys[index], &self.keys[index + 1]);
+        let d = b.time - a.time;
+        if d < EPSILON {
+            return b.value;
+        }
+
+        let t = (x - a.time) / d;
+        let mut value = T::zero();
+        for channel in 0..T::CHANNELS {
+            let (start, end) = (a.value.channel(channel), b.value.channel(channel));
+            let third = d as f64 / 3.0;
+            value.set_channel(
+                channel,
+                bezier_interpolate(
+                    start,
+                    start + third * a.right_tan.channel(channel),
+                    end - third * b.left_tan.channel(channel),
+                    end,
+                    t as f64,
+                ),
+            );
+        }
+
+        T::finish(a.value, b.value, t, value)
+    }
+
+    /// Recomputes the linear tangents of a key and the sides of its neighbors facing it.
+    fn update_auto_tangents(&mut self, index: usize) {
+        let secant = |a: &Key<T>, b: &Key<T>| {
+            const EPSILON: f32 = 0.00001;
+
+            let mut slope = T::zero();
+            let d = b.time - a.time;
+            if d >= EPSILON {
+                for channel in 0..T::CHANNELS {
+                    slope.set_channel(
+                        channel,
+                        (b.value.channel(channel) - a.value.channel(channel)) / d as f64,
+                    );
+                }
+            }
+            slope
+        };
+
+        if index > 0 {
+            let slope = secant(&self.keys[index - 1], &self.keys[index]);
+            if self.keys[index].left_mode == TangentMode::Linear {
+                self.keys[index].left_tan = slope;
+            }
+            if self.keys[index - 1].right_mode == TangentMode::Linear {
+                self.keys[index - 1].right_tan = slope;
+            }
+        }
+        if index + 1 < self.keys.len() {
+            let slope = secant(&self.keys[index], &self.keys[index + 1]);
+            if self.keys[index].right_mode == TangentMode::Linear {
+                self.keys[index].right_tan = slope;
+            }
+            if self.keys[index + 1].left_mode == TangentMode::Linear {
+                self.keys[index + 1].left_tan = slope;
+            }
+        }
+    }
+}
+
+impl<T: CurveValue> Sampleable<T> for KeyedCurve<T> {
+    fn sample(&self, x: f32) -> T {
+        KeyedCurve::sample(self, x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_between_keys() {
+        let curve = KeyedCurve::from_keys([Key::new(1.0, [2.0, -1.0]), Key::new(0.0, [0.0, 1.0])]);
+
+        assert_eq!(curve.sample(0.0), [0.0, 1.0]);
+        assert_eq!(curve.sample(0.5), [1.0, 0.0]);
+        assert_eq!(curve.sample(1.0), [2.0, -1.0]);
+
+        // Values are not clamped
+        let curve = KeyedCurve::from_keys([Key::new(0.0, -5.0f64), Key::new(1.0, 5.0)]);
+        assert_eq!(curve.sample(0.75), 2.5);
+    }
+
+    #[test]
+    fn holds_values_outside_keys() {
+        let curve = KeyedCurve::from_keys([Key::new(0.2, 1.0f32), Key::new(0.8, 3.0)]);
+
+        assert_eq!(curve.sample(0.0), 1.0);
+        assert_eq!(curve.sample(1.0), 3.0);
+        assert_eq!(curve.sample(f32::NEG_INFINITY), 1.0);
+        assert_eq!(curve.sample(f32::INFINITY), 3.0);
+        assert_eq!(curve.sample(f32::NAN), 1.0);
+    }
+
+    #[test]
+    fn samples_small_curves() {
+        let empty = KeyedCurve::<f32>::new();
+        assert_eq!(empty.sample(0.5), 0.0);
+        assert_eq!(empty.sample(f32::NAN), 0.0);
+
+        let single = KeyedCurve::from_keys([Key::new(0.5, Vec2::new(1.0, 2.0))]);
+        assert_eq!(single.sample(0.0), Vec2::new(1.0, 2.0));
+        assert_eq!(single.sample(f32::NAN), Vec2::new(1.0, 2.0));
+
+        // Keys at the same time jump
+        let step = KeyedCurve::from_keys([
+            Key::new(0.0, 0.0f32),
+            Key::new(0.5, 0.0),
+            Key::new(0.5, 1.0),
+            Key::new(1.0, 1.0),
+        ]);
+        assert_eq!(step.sample(0.25), 0.0);
+        assert_eq!(step.sample(0.75), 1.0);
+    }
+
+    #[test]
+    fn follows_free_tangents() {
+        let curve = KeyedCurve::from_keys([
+            Key::with_tangents(0.0, 0.0f32, 0.0, 0.0),
+            Key::with_tangents(1.0, 1.0, 0.0, 0.0),
+        ]);
+
+        assert!((curve.sample(0.5) - 0.5).abs() < 0.0001);
+        assert!(curve.sample(0.2) < 0.2);
+        assert!(curve.sample(0.8) > 0.8);
+    }
+
+    #[test]
+    fn edits_keys() {
+        let mut curve = KeyedCurve::from_keys([Key::new(0.0, 0.0f32), Key::new(1.0, 1.0)]);
+
+        assert_eq!(curve.add_key(Key::new(0.5, 0.0)), Some(1));
+        assert_eq!(curve.keys()[0].right_tan(), 0.0);
+        assert_eq!(curve.keys()[2].left_tan(), 2.0);
+
+        curve.set_time(1, 2.0);
+        assert_eq!(curve.keys()[1].time(), 1.0);
+        curve.set_time(1, f32::NAN);
+        assert_eq!(curve.keys()[1].time(), 1.0);
+
+        // Non-finite times are rejected, keeping the times sorted
+        assert_eq!(curve.add_key(Key::new(f32::NAN, 0.5)), None);
+        assert_eq!(curve.add_key(Key::new(f32::INFINITY, 0.5)), None);
+        assert_eq!(curve.keys().len(), 3);
+        assert_eq!(curve.add_key(Key::new(-1.0, 0.5)), Some(1));
+        assert_eq!(curve.remove_key(1).map(|key| key.time()), Some(0.0));
+
+        curve.set_right_tan(0, 0, 3.0);
+        assert!(curve.keys()[0].right_mode() == TangentMode::Free);
+        assert_eq!(curve.remove_key(1).map(|key| key.value()), Some(0.0));
+        assert!(curve.remove_key(5).is_none());
+    }
+
+    #[test]
+    fn slerps_rotations() {
+        let a = Quaternion::IDENTITY;
+        let b = Quaternion::from_axis_angle([0.0, 0.0, 2.0], core::f32::consts::FRAC_PI_2);
+        let curve = KeyedCurve::from_keys([Key::new(0.0, a), Key::new(1.0, b)]);
+
+        let half = curve.sample(0.5);
+        let expected = Quaternion::from_axis_angle([0.0, 0.0, 1.0], core::f32::consts::FRAC_PI_4);
+        assert!((half.dot(expected) - 1.0).abs() < 0.0001);
+        assert!((half.dot(half) - 1.0).abs() < 0.0001);
+    }
+}
@@ -0,0 +1,382 @@
+use egui::NumExt;
+
+use crate::editor::DragTarget;
+use crate::math::Rangef;
+use crate::{CurveValue, Key, KeyedCurve};
+
+#[derive(serde::Deserialize, serde::Serialize, Clone, Default)]
+struct KeyedCurveEditorState {
+    dragging: Option<DragTarget>,
+    /// Key and channel.
+    selected: Option<(usize, usize)>,
+}
+
+/// Edits a [`KeyedCurve`], drawing every channel in its own color over the same keys.
+///
+/// Dragging a handle moves its key in time and its channel in value. Clicking elsewhere adds a key,
+/// setting the closest channel to where it was clicked.
+pub struct KeyedCurveEditor<'a, T> {
+    curve: &'a mut KeyedCurve<T>,
+    min_size: egui::Vec2,
+    max_size: Option<egui::Vec2>,
+    width: Option<f32>,
+    height: Option<f32>,
+    view_aspect: f32,
+    value_range: Rangef,
+}
+
+#[allow(unused)]
+impl<'a, T: CurveValue> KeyedCurveEditor<'a, T> {
+    pub fn new(curve: &'a mut KeyedCurve<T>) -> Self {
+        Self {
+            curve,
+            min_size: egui::vec2(40.0, 40.0),
+            max_size: None,
+            width: None,
+            height: None,
+            view_aspect: 13.0 / 6.0,
+            value_range: T::default_range(),
+        }
+    }
+
+    pub fn with_min_size(self, min_size: egui::Vec2) -> Self {
+        Self { min_size, ..self }
+    }
+
+    pub fn with_max_size(self, max_size: egui::Vec2) -> Self {
+        Self {
+            max_size: Some(max_size),
+            ..self
+        }
+    }
+
+    pub fn with_width(self, width: f32) -> Self {
+        Self {
+            width: Some(width),
+            ..self
+        }
+    }
+
+    pub fn with_height(self, height: f32) -> Self {
+        Self {
+            height: Some(height),
+            ..self
+        }
+    }
+
+    pub fn with_size(self, size: egui::Vec2) -> Self {
+        Self {
+            width: Some(size.x),
+            height: Some(size.y),
+            ..self
+        }
+    }
+
+    pub fn with_aspect(self, view_aspect: f32) -> Self {
+        Self {
+            view_aspect,
+            ..self
+        }
+    }
+
+    /// Values from the bottom to the top of the plot.
+    pub fn with_value_range(self, value_range: impl Into<Rangef>) -> Self {
+        Self {
+            value_range: value_range.into(),
+            ..self
+        }
+    }
+
+    fn load_state(ctx: &egui::Context, id: egui::Id) -> Option<KeyedCurveEditorState> {
+        ctx.data_mut(|d| d.get_persisted(id))
+    }
+
+    fn store_state(ctx: &egui::Context, id: egui::Id, state: KeyedCurveEditorState) {
+        ctx.data_mut(|d| d.insert_persisted(id, state));
+    }
+
+    fn plot_coords(&self, plot_rect: egui::Rect, time: f32, value: f64) -> egui::Pos2 {
+        let y = (value as f32 - self.value_range.min) / self.value_range.span();
+
+        plot_rect.lerp_inside(egui::vec2(time, 1.0 - y))
+    }
+
+    fn curve_coords(&self, plot_rect: egui::Rect, pos: egui::Pos2) -> (f32, f64) {
+        let time = (pos.x - plot_rect.left()) / plot_rect.width();
+        let y = 1.0 - (pos.y - plot_rect.top()) / plot_rect.height();
+
+        (
+            time,
+            (self.value_range.min + y * self.value_range.span()) as f64,
+        )
+    }
+
+    /// Slopes are drawn in the normalized space of the plot, like the ones of `CurveEditor`.
+    fn tangent_plot_coords(
+        &self,
+        plot_rect: egui::Rect,
+        key: &Key<T>,
+        channel: usize,
+    ) -> (egui::Pos2, egui::Pos2) {
+        let span = self.value_range.span() as f64;
+        let left = (key.left_tan().channel(channel) / span) as f32;
+        let right = (key.right_tan().channel(channel) / span) as f32;
+
+        let left_dir = -egui::vec2(1.0, -left).normalized();
+        let right_dir = egui::vec2(1.0, -right).normalized();
+
+        let plot_pos = self.plot_coords(plot_rect, key.time(), key.value().channel(channel));
+
+        (plot_pos + left_dir * 20.0, plot_pos + right_dir * 20.0)
+    }
+}
+
+impl<T: CurveValue> egui::Widget for KeyedCurveEditor<'_, T> {
+    fn ui(self, ui: &mut egui::Ui) -> egui::Response {
+        // Determine position of widget.
+        let pos = ui.available_rect_before_wrap().min;
+        // Minimum values for screen protection
+        let mut min_size = self.min_size;
+        min_size.x = min_size.x.at_least(1.0);
+        min_size.y = min_size.y.at_least(1.0);
+
+        // Determine size of widget.
+        let size = {
+            let mut width = self
+                .width
+                .unwrap_or_else(|| {
+                    if let Some(height) = self.height {
+                        height * self.view_aspect
+                    } else {
+                        ui.available_size_before_wrap().x
+                    }
+                })
+                .at_least(min_size.x);
+
+            let mut height = self
+                .height
+                .unwrap_or_else(|| width / self.view_aspect)
+                .at_least(min_size.y);
+
+            if let Some(max_size) = self.max_size {
+                width = width.min(max_size.x);
+                height = height.min(max_size.y);
+            }
+
+            egui::vec2(width, height)
+        };
+
+        let plot_rect = egui::Rect {
+            min: pos,
+            max: pos + size,
+        };
+
+        let id = ui.next_auto_id();
+        let response = ui.allocate_rect(plot_rect, egui::Sense::click_and_drag());
+        ui.ctx().check_for_id_clash(id, plot_rect, "Plot");
+
+        // Background
+        ui.painter()
+            .with_clip_rect(plot_rect)
+            .add(egui::epaint::RectShape::new(
+                plot_rect,
+                2,
+                ui.visuals().extreme_bg_color,
+                ui.visuals().widgets.noninteractive.bg_stroke,
+                egui::StrokeKind::Inside,
+            ));
+
+        let mut state = Self::load_state(ui.ctx(), id).unwrap_or_default();
+
+        // Drop a selection the curve no longer has
+        if state
+            .selected
+            .is_some_and(|(index, _)| index >= self.curve.keys().len())
+        {
+            state.selected = None;
+            state.dragging = None;
+        }
+
+        if (response.clicked() || response.secondary_clicked() || response.dragged())
+            && response.hover_pos().is_some()
+            && state.dragging.is_none()
+        {
+            let pos = response.hover_pos().unwrap();
+
+            let mut handles = vec![];
+            for (index, key) in self.curve.keys().iter().enumerate() {
+                for channel in 0..T::CHANNELS {
+                    handles.push((
+                        DragTarget::Handle,
+                        (index, channel),
+                        self.plot_coords(plot_rect, key.time(), key.value().channel(channel)),
+                    ));
+                }
+            }
+            if let Some((index, channel)) = state.selected {
+                let (left, right) =
+                    self.tangent_plot_coords(plot_rect, &self.curve.keys()[index], channel);
+                handles.push((DragTarget::LeftTangent, (index, channel), left));
+                handles.push((DragTarget::RightTangent, (index, channel), right));
+            }
+
+            // Tangents of the selection come first, they are the smaller targets
+            let near = handles
+                .iter()
+                .rev()
+                .find(|(_, _, handle_pos)| handle_pos.distance(pos) < 15.0);
+
+            if let Some(&(drag_type, selected, _)) = near {
+                if response.clicked_by(egui::PointerButton::Primary)
+                    || response.dragged_by(egui::PointerButton::Primary)
+                {
+                    state.dragging = Some(drag_type);
+                    state.selected = Some(selected);
+                }
+
+                // Remove key?
+                if response.secondary_clicked()
+                    && drag_type == DragTarget::Handle
+                    && self.curve.keys().len() > 1
+                {
+                    self.curve.remove_key(selected.0);
+                    state.dragging = None;
+                    state.selected = None;
+                }
+            } else if response.clicked_by(egui::PointerButton::Primary)
+                || response.dragged_by(egui::PointerButton::Primary)
+            {
+                // Add key, with the closest channel going through the pointer
+                let (time, value) = self.curve_coords(plot_rect, pos);
+                let time = time.clamp(0.0, 1.0);
+                let sampled = self.curve.sample(time);
+                let channel = (0..T::CHANNELS)
+                    .min_by(|&a, &b| {
+                        (sampled.channel(a) - value)
+                            .abs()
+                            .total_cmp(&(sampled.channel(b) - value).abs())
+                    })
+                    .unwrap_or(0);
+
+                let mut key_value = sampled;
+                key_value.set_channel(channel, value);
+                if let Some(index) = self.curve.add_key(Key::new(time, key_value)) {
+                    state.selected = Some((index, channel));
+                }
+            }
+        }
+
+        // Stop dragging?
+        if state.dragging.is_some()
+            && (response.drag_stopped() || !response.is_pointer_button_down_on())
+        {
+            state.dragging = None;
+        }
+
+        // Desellect?
+        if response.clicked_elsewhere() {
+            state.selected = None;
+        }
+
+        // Handle dragging
+        if let (Some((index, channel)), Some(drag_type)) = (state.selected, state.dragging) {
+            let key = self.curve.keys()[index];
+            let key_pos = self.plot_coords(plot_rect, key.time(), key.value().channel(channel));
+            let span = self.value_range.span() as f64;
+
+            match drag_type {
+                DragTarget::Handle => {
+                    let screen_pos = (key_pos + response.drag_delta())
+                        .clamp(plot_rect.left_top(), plot_rect.right_bottom());
+                    let (time, value) = self.curve_coords(plot_rect, screen_pos);
+
+                    self.curve.set_time(index, time);
+                    self.curve.set_channel(index, channel, value);
+                }
+                DragTarget::LeftTangent => {
+                    let (plot_tangent, _) = self.tangent_plot_coords(plot_rect, &key, channel);
+                    let mut screen_tan = plot_tangent + response.drag_delta();
+                    screen_tan.x = screen_tan.x.min(key_pos.x);
+
+                    let tangent_dir = (screen_tan - key_pos).normalized();
+                    let tangent = (-tangent_dir.y / tangent_dir.x) as f64 * span;
+                    if tangent.is_finite() {
+                        self.curve.set_left_tan(index, channel, tangent);
+                    }
+                }
+                DragTarget::RightTangent => {
+                    let (_, plot_tangent) = self.tangent_plot_coords(plot_rect, &key, channel);
+                    let mut screen_tan = plot_tangent + response.drag_delta();
+                    screen_tan.x = screen_tan.x.max(key_pos.x);
+
+                    let tangent_dir = (screen_tan - key_pos).normalized();
+                    let tangent = (-tangent_dir.y / tangent_dir.x) as f64 * span;
+                    if tangent.is_finite() {
+                        self.curve.set_right_tan(index, channel, tangent);
+                    }
+                }
+            }
+        }
+
+        let painter = ui.painter().with_clip_rect(plot_rect);
+        let visuals = ui.style().interact(&response);
+
+        // Channels
+        let resolution = plot_rect.width().ceil().at_least(1.0) as usize;
+        let samples: Vec<(f32, T)> = (0..=resolution)
+            .map(|i| {
+                let time = i as f32 / resolution as f32;
+                (time, self.curve.sample(time))
+            })
+            .collect();
+        for channel in 0..T::CHANNELS {
+            painter.add(egui::epaint::PathShape::line(
+                samples
+                    .iter()
+                    .map(|(time, value)| self.plot_coords(plot_rect, *time, value.channel(channel)))
+                    .collect(),
+                egui::Stroke::new(1.5, T::channel_color(channel)),
+            ));
+        }
+
+        // Draw tangents
+        if let Some((index, channel)) = state.selected {
+            let key = &self.curve.keys()[index];
+            let plot_pos = self.plot_coords(plot_rect, key.time(), key.value().channel(channel));
+            let (plot_left, plot_right) = self.tangent_plot_coords(plot_rect, key, channel);
+
+            for tangent in [plot_left, plot_right] {
+                painter.line_segment([tangent, plot_pos], visuals.fg_stroke);
+                painter.add(egui::epaint::CircleShape {
+                    center: tangent,
+                    radius: 3.5,
+                    fill: visuals.bg_fill,
+                    stroke: visuals.fg_stroke,
+                });
+            }
+        }
+
+        for (index, key) in self.curve.keys().iter().enumerate() {
+            for channel in 0..T::CHANNELS {
+                let fill = if state.selected == Some((index, channel)) {
+                    ui.visuals().selection.bg_fill
+                } else {
+                    visuals.bg_fill
+                };
+
+                painter.add(egui::epaint::CircleShape {
+                    center: self.plot_coords(plot_rect, key.time(), key.value().channel(channel)),
+                    radius: 5.0,
+                    fill,
+                    stroke: egui::Stroke::new(1.5, T::channel_color(channel)),
+                });
+            }
+        }
+
+        Self::store_state(ui.ctx(), id, state);
+
+        ui.advance_cursor_after_rect(plot_rect);
+
+        response
+    }
+}
@@ -0,0 +1,379 @@
+// Plain vector types for the curve model, so that it does not need egui.
+//
+// They mirror the parts of `emath` the model uses, and convert to and from it with the `egui`
+// feature. They are not exported from the crate root, where they would clash with the ones of
+// `egui` for glob imports.
+
+use core::ops::{Add, AddAssign, Div, Index, IndexMut, Mul, Neg, Sub, SubAssign};
+
+#[allow(unused_imports)]
+use crate::prelude::*;
+
+pub use crate::float::Float;
+
+/// A position in curve space, in `f32` unless another [`Float`] is given.
+#[derive(PartialEq, Clone, Copy, Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct Pos2<S = f32> {
+    pub x: S,
+    pub y: S,
+}
+
+/// A position in curve space with `f64` precision, see [`crate::Curve64`].
+pub type DPos2 = Pos2<f64>;
+
+pub const fn pos2(x: f32, y: f32) -> Pos2 {
+    Pos2 { x, y }
+}
+
+pub const fn dpos2(x: f64, y: f64) -> DPos2 {
+    Pos2 { x, y }
+}
+
+#[allow(unused)]
+impl<S: Float> Pos2<S> {
+    pub const ZERO: Self = Self::new(S::ZERO, S::ZERO);
+
+    pub const fn new(x: S, y: S) -> Self {
+        Self { x, y }
+    }
+
+    pub fn to_vec2(self) -> Vec2<S> {
+        Vec2::new(self.x, self.y)
+    }
+
+    /// Converts to another precision, rounding to the nearest value.
+    pub fn cast<T: Float>(self) -> Pos2<T> {
+        Pos2::new(T::from_f64(self.x.to_f64()), T::from_f64(self.y.to_f64()))
+    }
+
+    pub fn distance(self, other: Self) -> S {
+        (self - other).length()
+    }
+
+    pub fn clamp(self, min: Self, max: Self) -> Self {
+        Self::new(self.x.clamp(min.x, max.x), self.y.clamp(min.y, max.y))
+    }
+
+    pub fn is_finite(self) -> bool {
+        self.x.is_finite() && self.y.is_finite()
+    }
+}
+
+#[allow(unused)]
+impl DPos2 {
+    /// Rounds to the nearest `f32` position.
+    pub fn to_pos2(self) -> Pos2 {
+        self.cast()
+    }
+}
+
+impl From<Pos2> for DPos2 {
+    fn from(pos: Pos2) -> Self {
+        pos.cast()
+    }
+}
+
+/// A direction or size in curve space, in `f32` unless another [`Float`] is given.
+#[derive(PartialEq, Clone, Copy, Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct Vec2<S = f32> {
+    pub x: S,
+    pub y: S,
+}
+
+pub const fn vec2(x: f32, y: f32) -> Vec2 {
+    Vec2 { x, y }
+}
+
+#[allow(unused)]
+impl<S: Float> Vec2<S> {
+    pub const ZERO: Self = Self::new(S::ZERO, S::ZERO);
+
+    pub const fn new(x: S, y: S) -> Self {
+        Self { x, y }
+    }
+
+    pub const fn splat(value: S) -> Self {
+        Self::new(value, value)
+    }
+
+    pub fn to_pos2(self) -> Pos2<S> {
+        Pos2::new(self.x, self.y)
+    }
+
+    pub fn length(self) -> S {
+        self.x.hypot(self.y)
+    }
+
+    /// Returns a zero vector for a zero vector.
+    pub fn normalized(self) -> Self {
+        let length = self.length();
+        if length == S::ZERO {
+            return Self::ZERO;
+        }
+
+        self / length
+    }
+
+    pub fn is_finite(self) -> bool {
+        self.x.is_finite() && self.y.is_finite()
+    }
+}
+
+/// An inclusive range of values.
+#[derive(PartialEq, Clone, Copy, Debug, serde::Deserialize, serde::Serialize)]
+pub struct Rangef<S = f32> {
+    pub min: S,
+    pub max: S,
+}
+
+#[allow(unused)]
+impl<S: Float> Rangef<S> {
+    /// The range containing nothing, ready to be grown by `min` and `max`.
+    pub const NOTHING: Self = Self::new(S::INFINITY, S::NEG_INFINITY);
+
+    pub const fn new(min: S, max: S) -> Self {
+        Self { min, max }
+    }
+
+    pub const fn point(value: S) -> Self {
+        Self::new(value, value)
+    }
+
+    pub fn span(self) -> S {
+        self.max - self.min
+    }
+
+    pub fn center(self) -> S {
+        (self.min + self.max) * S::from_f32(0.5)
+    }
+
+    pub fn contains(self, value: S) -> bool {
+        self.min <= value && value <= self.max
+    }
+}
+
+/// An axis aligned rectangle.
+#[derive(PartialEq, Clone, Copy, Debug, serde::Deserialize, serde::Serialize)]
+pub struct Rect<S = f32> {
+    pub min: Pos2<S>,
+    pub max: Pos2<S>,
+}
+
+#[allow(unused)]
+impl<S: Float> Rect<S> {
+    /// The rectangle containing nothing, ready to be grown by points.
+    pub const NOTHING: Self = Self {
+        min: Pos2::new(S::INFINITY, S::INFINITY),
+        max: Pos2::new(S::NEG_INFINITY, S::NEG_INFINITY),
+    };
+
+    pub const fn from_min_max(min: Pos2<S>, max: Pos2<S>) -> Self {
+        Self { min, max }
+    }
+
+    pub fn from_x_y_ranges(x: Rangef<S>, y: Rangef<S>) -> Self {
+        Self::from_min_max(Pos2::new(x.min, y.min), Pos2::new(x.max, y.max))
+    }
+
+    pub fn from_two_pos(a: Pos2<S>, b: Pos2<S>) -> Self {
+        Self::from_min_max(
+            Pos2::new(a.x.min(b.x), a.y.min(b.y)),
+            Pos2::new(a.x.max(b.x), a.y.max(b.y)),
+        )
+    }
+
+    /// The smallest rectangle containing every point.
+    pub fn from_points(points: &[Pos2<S>]) -> Self {
+        let mut rect = Self::NOTHING;
+        for point in points {
+            rect.min = Pos2::new(rect.min.x.min(point.x), rect.min.y.min(point.y));
+            rect.max = Pos2::new(rect.max.x.max(point.x), rect.max.y.max(point.y));
+        }
+
+        rect
+    }
+
+    pub fn x_range(self) -> Rangef<S> {
+        Rangef::new(self.min.x, self.max.x)
+    }
+
+    pub fn y_range(self) -> Rangef<S> {
+        Rangef::new(self.min.y, self.max.y)
+    }
+
+    pub fn width(self) -> S {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(self) -> S {
+        self.max.y - self.min.y
+    }
+
+    pub fn center(self) -> Pos2<S> {
+        let half = S::from_f32(0.5);
+        Pos2::new(
+            (self.min.x + self.max.x) * half,
+            (self.min.y + self.max.y) * half,
+        )
+    }
+}
+
+impl<S: Float> Sub for Pos2<S> {
+    type Output = Vec2<S>;
+
+    fn sub(self, other: Pos2<S>) -> Vec2<S> {
+        Vec2::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl<S: Float> Add<Vec2<S>> for Pos2<S> {
+    type Output = Pos2<S>;
+
+    fn add(self, other: Vec2<S>) -> Pos2<S> {
+        Pos2::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl<S: Float> Sub<Vec2<S>> for Pos2<S> {
+    type Output = Pos2<S>;
+
+    fn sub(self, other: Vec2<S>) -> Pos2<S> {
+        Pos2::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl<S: Float> AddAssign<Vec2<S>> for Pos2<S> {
+    fn add_assign(&mut self, other: Vec2<S>) {
+        *self = *self + other;
+    }
+}
+
+impl<S: Float> SubAssign<Vec2<S>> for Pos2<S> {
+    fn sub_assign(&mut self, other: Vec2<S>) {
+        *self = *self - other;
+    }
+}
+
+impl<S: Float> Add for Vec2<S> {
+    type Output = Vec2<S>;
+
+    fn add(self, other: Vec2<S>) -> Vec2<S> {
+        Vec2::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl<S: Float> Sub for Vec2<S> {
+    type Output = Vec2<S>;
+
+    fn sub(self, other: Vec2<S>) -> Vec2<S> {
+        Vec2::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl<S: Float> Neg for Vec2<S> {
+    type Output = Vec2<S>;
+
+    fn neg(self) -> Vec2<S> {
+        Vec2::new(-self.x, -self.y)
+    }
+}
+
+/// Component-wise.
+impl<S: Float> Mul for Vec2<S> {
+    type Output = Vec2<S>;
+
+    fn mul(self, other: Vec2<S>) -> Vec2<S> {
+        Vec2::new(self.x * other.x, self.y * other.y)
+    }
+}
+
+impl<S: Float> Mul<S> for Vec2<S> {
+    type Output = Vec2<S>;
+
+    fn mul(self, factor: S) -> Vec2<S> {
+        Vec2::new(self.x * factor, self.y * factor)
+    }
+}
+
+impl<S: Float> Div<S> for Vec2<S> {
+    type Output = Vec2<S>;
+
+    fn div(self, divisor: S) -> Vec2<S> {
+        Vec2::new(self.x / divisor, self.y / divisor)
+    }
+}
+
+impl<S> Index<usize> for Vec2<S> {
+    type Output = S;
+
+    fn index(&self, index: usize) -> &S {
+        match index {
+            0 => &self.x,
+            1 => &self.y,
+            _ => panic!("Vec2 index out of bounds: {index}"),
+        }
+    }
+}
+
+impl<S> IndexMut<usize> for Vec2<S> {
+    fn index_mut(&mut self, index: usize) -> &mut S {
+        match index {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            _ => panic!("Vec2 index out of bounds: {index}"),
+        }
+    }
+}
+
+#[cfg(feature = "egui")]
+mod egui_conversions {
+    use super::{pos2, vec2, Pos2, Rangef, Rect, Vec2};
+
+    impl From<Pos2> for egui::Pos2 {
+        fn from(pos: Pos2) -> Self {
+            egui::pos2(pos.x, pos.y)
+        }
+    }
+
+    impl From<egui::Pos2> for Pos2 {
+        fn from(pos: egui::Pos2) -> Self {
+            pos2(pos.x, pos.y)
+        }
+    }
+
+    impl From<Vec2> for egui::Vec2 {
+        fn from(vec: Vec2) -> Self {
+            egui::vec2(vec.x, vec.y)
+        }
+    }
+
+    impl From<egui::Vec2> for Vec2 {
+        fn from(vec: egui::Vec2) -> Self {
+            vec2(vec.x, vec.y)
+        }
+    }
+
+    impl From<Rangef> for egui::Rangef {
+        fn from(range: Rangef) -> Self {
+            egui::Rangef::new(range.min, range.max)
+        }
+    }
+
+    impl From<egui::Rangef> for Rangef {
+        fn from(range: egui::Rangef) -> Self {
+            Rangef::new(range.min, range.max)
+        }
+    }
+
+    impl From<Rect> for egui::Rect {
+        fn from(rect: Rect) -> Self {
+            egui::Rect::from_min_max(rect.min.into(), rect.max.into())
+        }
+    }
+
+    impl From<egui::Rect> for Rect {
+        fn from(rect: egui::Rect) -> Self {
+            Rect::from_min_max(rect.min.into(), rect.max.into())
+        }
+    }
+}
@@ -0,0 +1,514 @@
+// Plays a curve over time to drive values at runtime, like a tween.
+
+use crate::math::Rangef;
+use crate::prelude::*;
+use crate::{Curve, ParseError};
+
+/// What happens when a [`CurvePlayer`] reaches the end of its curve.
+#[derive(PartialEq, Clone, Copy, Default, Debug, serde::Deserialize, serde::Serialize)]
+pub enum PlayMode {
+    /// Stops at the end.
+    #[default]
+    Once,
+    /// Starts over from the beginning.
+    Loop,
+    /// Plays backwards to the beginning, then forwards again.
+    PingPong,
+}
+
+/// What happened during one [`CurvePlayer::advance`].
+#[derive(PartialEq, Clone, Copy, Default, Debug)]
+pub struct PlayerEvents {
+    /// The delay ran out and the curve started playing.
+    pub started: bool,
+    /// How many times the curve looped, or turned around when playing ping-pong.
+    pub looped: u32,
+    /// The curve reached its end and the player stopped, only when playing once.
+    pub completed: bool,
+}
+
+/// Something with named values a [`CurvePlayer`] can set, see [`CurvePlayer::with_property`].
+pub trait AnimationTarget {
+    fn set_property(&mut self, name: &str, value: f32);
+}
+
+impl AnimationTarget for alloc::collections::BTreeMap<String, f32> {
+    fn set_property(&mut self, name: &str, value: f32) {
+        self.insert(name.to_string(), value);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S: core::hash::BuildHasher> AnimationTarget for std::collections::HashMap<String, f32, S> {
+    fn set_property(&mut self, name: &str, value: f32) {
+        self.insert(name.to_string(), value);
+    }
+}
+
+/// Plays a [`Curve`] over `duration` seconds, mapping its values to a range.
+///
+/// Deserializing fails on durations that are not positive and finite, and on negative speeds,
+/// delays or times.
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
+#[serde(try_from = "CurvePlayerData")]
+pub struct CurvePlayer {
+    curve: Curve,
+    duration: f32,
+    speed: f32,
+    mode: PlayMode,
+    delay: f32,
+    range: Rangef,
+    properties: Vec<(String, Rangef)>,
+
+    /// Seconds into the current cycle, which is twice the duration when playing ping-pong.
+    elapsed: f32,
+    delay_left: f32,
+    playing: bool,
+    started: bool,
+    finished: bool,
+}
+
+/// The serialized fields of a [`CurvePlayer`], checked before use.
+#[derive(serde::Deserialize)]
+struct CurvePlayerData {
+    curve: Curve,
+    duration: f32,
+    speed: f32,
+    mode: PlayMode,
+    delay: f32,
+    range: Rangef,
+    properties: Vec<(String, Rangef)>,
+    elapsed: f32,
+    delay_left: f32,
+    playing: bool,
+    started: bool,
+    finished: bool,
+}
+
+impl TryFrom<CurvePlayerData> for CurvePlayer {
+    type Error = ParseError;
+
+    fn try_from(data: CurvePlayerData) -> Result<Self, ParseError> {
+        if !(data.duration.is_finite() && data.duration > 0.0) {
+            return Err(ParseError::new(format!(
+                "invalid duration `{}`, it must be positive",
+                data.duration
+            )));
+        }
+
+        let non_negative = |value: f32| value.is_finite() && value >= 0.0;
+        for (name, value) in [
+            ("speed", data.speed),
+            ("delay", data.delay),
+            ("elapsed time", data.elapsed),
+            ("delay left", data.delay_left),
+        ] {
+            if !non_negative(value) {
+                return Err(ParseError::new(format!(
+                    "invalid {name} `{value}`, it must not be negative"
+                )));
+            }
+        }
+
+        Ok(Self {
+            curve: data.curve,
+            duration: data.duration,
+            speed: data.speed,
+            mode: data.mode,
+            delay: data.delay,
+            range: data.range,
+            properties: data.properties,
+            elapsed: data.elapsed,
+            delay_left: data.delay_left,
+            playing: data.playing,
+            started: data.started,
+            finished: data.finished,
+        })
+    }
+}
+
+#[allow(unused)]
+impl CurvePlayer {
+    /// A player that starts playing right away, durations are kept positive.
+    pub fn new(curve: Curve, duration: f32) -> Self {
+        Self {
+            curve,
+            duration: duration.max(f32::EPSILON),
+            speed: 1.0,
+            mode: PlayMode::default(),
+            delay: 0.0,
+            range: Rangef::new(0.0, 1.0),
+            properties: vec![],
+            elapsed: 0.0,
+            delay_left: 0.0,
+            playing: true,
+            started: false,
+            finished: false,
+        }
+    }
+
+    /// Scales time, negative speeds are treated as zero.
+    pub fn with_speed(self, speed: f32) -> Self {
+        Self {
+            speed: speed.max(0.0),
+            ..self
+        }
+    }
+
+    pub fn with_mode(self, mode: PlayMode) -> Self {
+        Self { mode, ..self }
+    }
+
+    /// Seconds to wait before playing, not scaled by the speed.
+    pub fn with_delay(self, delay: f32) -> Self {
+        let delay = delay.max(0.0);
+
+        Self {
+            delay,
+            delay_left: delay,
+            ..self
+        }
+    }
+
+    /// Maps the values of the curve from `0..=1` to `range`.
+    pub fn with_range(self, range: impl Into<Rangef>) -> Self {
+        Self {
+            range: range.into(),
+            ..self
+        }
+    }
+
+    /// Adds a named property set by [`CurvePlayer::apply_properties`], with its own range.
+    pub fn with_property(mut self, name: impl Into<String>, range: impl Into<Rangef>) -> Self {
+        self.properties.push((name.into(), range.into()));
+        self
+    }
+
+    pub fn curve(&self) -> &Curve {
+        &self.curve
+    }
+
+    pub fn curve_mut(&mut self) -> &mut Curve {
+        &mut self.curve
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed.max(0.0);
+    }
+
+    pub fn mode(&self) -> PlayMode {
+        self.mode
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    pub fn play(&mut self) {
+        self.playing = !self.finished;
+    }
+
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    /// Goes back to the beginning, delay included, and plays.
+    pub fn restart(&mut self) {
+        self.elapsed = 0.0;
+        self.delay_left = self.delay;
+        self.playing = true;
+        self.started = false;
+        self.finished = false;
+    }
+
+    /// Jumps to `seconds` into the curve, skipping what is left of the delay.
+    pub fn seek(&mut self, seconds: f32) {
+        self.elapsed = seconds.clamp(0.0, self.duration);
+        self.delay_left = 0.0;
+        self.started = true;
+        self.finished = self.mode == PlayMode::Once && self.elapsed >= self.duration;
+        self.playing &= !self.finished;
+    }
+
+    /// Where the player is on the curve, in `0..=1`.
+    pub fn progress(&self) -> f32 {
+        let progress = self.elapsed / self.duration;
+
+        if progress > 1.0 {
+            // Coming back when playing ping-pong
+            (2.0 - progress).max(0.0)
+        } else {
+            progress
+        }
+    }
+
+    /// The value of the curve at [`CurvePlayer::progress`], mapped to the range.
+    pub fn value(&self) -> f32 {
+        self.value_in(self.range)
+    }
+
+    /// Moves time forward by `delta` seconds.
+    pub fn advance(&mut self, delta: f32) -> PlayerEvents {
+        let mut events = PlayerEvents::default();
+        if !self.playing || !delta.is_finite() {
+            return events;
+        }
+
+        let mut delta = delta.max(0.0);
+        if self.delay_left > 0.0 {
+            let waited = delta.min(self.delay_left);
+            self.delay_left -= waited;
+            delta -= waited;
+
+            if self.delay_left > 0.0 {
+                return events;
+            }
+        }
+
+        if !self.started {
+            self.started = true;
+            events.started = true;
+        }
+
+        let before = self.elapsed;
+        self.elapsed += delta * self.speed;
+
+        match self.mode {
+            PlayMode::Once => {
+                if self.elapsed >= self.duration {
+                    self.elapsed = self.duration;
+                    self.playing = false;
+                    self.finished = true;
+                    events.completed = true;
+                }
+            }
+            PlayMode::Loop => {
+                let loops = (self.elapsed / self.duration).floor();
+                self.elapsed -= loops * self.duration;
+                events.looped = loops as u32;
+            }
+            PlayMode::PingPong => {
+                // Turning around at either end counts as a loop
+                let turns =
+                    (self.elapsed / self.duration).floor() - (before / self.duration).floor();
+                events.looped = turns as u32;
+                self.elapsed %= 2.0 * self.duration;
+            }
+        }
+
+        events
+    }
+
+    /// Advances, then sets `target` to the new value.
+    pub fn drive(&mut self, delta: f32, target: &mut f32) -> PlayerEvents {
+        let events = self.advance(delta);
+        self.apply(target);
+
+        events
+    }
+
+    pub fn apply(&self, target: &mut f32) {
+        *target = self.value();
+    }
+
+    /// Sets every property added with [`CurvePlayer::with_property`] on `target`.
+    pub fn apply_properties(&self, target: &mut impl AnimationTarget) {
+        for (name, range) in &self.properties {
+            target.set_property(name, self.value_in(*range));
+        }
+    }
+
+    fn value_in(&self, range: Rangef) -> f32 {
+        range.min + range.span() * self.curve.sample(self.progress())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::pos2;
+    use crate::Point;
+
+    fn line() -> Curve {
+        Curve::from_points([
+            Point::from_pos(pos2(0.0, 0.0)),
+            Point::from_pos(pos2(1.0, 1.0)),
+        ])
+    }
+
+    fn assert_close(a: f32, b: f32) {
+        assert!((a - b).abs() < 0.0001, "{a} != {b}");
+    }
+
+    #[test]
+    fn plays_once() {
+        let mut player = CurvePlayer::new(line(), 2.0).with_range(Rangef::new(10.0, 20.0));
+
+        let events = player.advance(0.5);
+        assert!(events.started && !events.completed);
+        assert_close(player.progress(), 0.25);
+        assert_close(player.value(), 12.5);
+
+        let events = player.advance(1.5);
+        assert!(!events.started && events.completed);
+        assert!(player.is_finished() && !player.is_playing());
+        assert_eq!(player.progress(), 1.0);
+
+        // Finished players stay put
+        assert_eq!(player.advance(1.0), PlayerEvents::default());
+        player.play();
+        assert!(!player.is_playing());
+    }
+
+    #[test]
+    fn loops_at_the_end() {
+        let mut player = CurvePlayer::new(line(), 1.0).with_mode(PlayMode::Loop);
+
+        let events = player.advance(1.0);
+        assert_eq!(events.looped, 1);
+        assert_eq!(player.progress(), 0.0);
+
+        let events = player.advance(2.25);
+        assert_eq!(events.looped, 2);
+        assert_close(player.progress(), 0.25);
+        assert!(player.is_playing() && !player.is_finished());
+    }
+
+    #[test]
+    fn ping_pongs() {
+        let mut player = CurvePlayer::new(line(), 1.0).with_mode(PlayMode::PingPong);
+
+        assert_eq!(player.advance(0.75).looped, 0);
+        assert_close(player.progress(), 0.75);
+
+        // Turning around at the end
+        assert_eq!(player.advance(0.5).looped, 1);
+        assert_close(player.progress(), 0.75);
+
+        // And at the beginning
+        assert_eq!(player.advance(0.75).looped, 1);
+        assert_close(player.progress(), 0.0);
+        assert_eq!(player.advance(1.0).looped, 1);
+        assert_close(player.progress(), 1.0);
+    }
+
+    #[test]
+    fn waits_for_the_delay() {
+        let mut player = CurvePlayer::new(line(), 1.0)
+            .with_delay(0.5)
+            .with_speed(2.0);
+
+        let events = player.advance(0.25);
+        assert!(!events.started);
+        assert_eq!(player.progress(), 0.0);
+
+        // What is left after the delay plays at speed
+        let events = player.advance(0.5);
+        assert!(events.started);
+        assert_close(player.progress(), 0.5);
+
+        player.restart();
+        assert_eq!(player.progress(), 0.0);
+        assert!(!player.advance(0.25).started);
+    }
+
+    #[test]
+    fn ignores_invalid_input() {
+        let player = CurvePlayer::new(line(), -1.0).with_speed(-2.0);
+        assert!(player.duration() > 0.0);
+        assert_eq!(player.speed(), 0.0);
+
+        let mut player = CurvePlayer::new(line(), 1.0);
+        assert_eq!(player.advance(f32::NAN), PlayerEvents::default());
+        assert_eq!(player.advance(f32::INFINITY), PlayerEvents::default());
+        assert_eq!(player.advance(-1.0).looped, 0);
+        assert_eq!(player.progress(), 0.0);
+
+        player.pause();
+        player.advance(0.5);
+        assert_eq!(player.progress(), 0.0);
+    }
+
+    #[test]
+    fn deserializes_checked_players() {
+        let player = CurvePlayer::new(line(), 2.0)
+            .with_speed(0.5)
+            .with_delay(1.0)
+            .with_mode(PlayMode::Loop);
+        let player: CurvePlayer =
+            serde_json::from_str(&serde_json::to_string(&player).unwrap()).unwrap();
+        assert_eq!(player.duration(), 2.0);
+        assert_eq!(player.speed(), 0.5);
+
+        let text = serde_json::to_string(&CurvePlayer::new(line(), 1.0)).unwrap();
+        for (field, value) in [
+            ("duration", "0.0"),
+            ("duration", "-1.0"),
+            ("speed", "-2.0"),
+            ("delay", "-1.0"),
+            ("elapsed", "-0.5"),
+        ] {
+            let mut json: serde_json::Value = serde_json::from_str(&text).unwrap();
+            json[field] = serde_json::from_str(value).unwrap();
+            let error = serde_json::from_value::<CurvePlayer>(json).err();
+            assert!(error.is_some(), "{field}: {value}");
+        }
+
+        let text = ron::to_string(&CurvePlayer::new(line(), 1.0))
+            .unwrap()
+            .replace("speed:1.0", "speed:inf");
+        let error = ron::from_str::<CurvePlayer>(&text).err().unwrap();
+        assert!(error.to_string().contains("invalid speed"), "{error}");
+    }
+
+    #[test]
+    fn seeks() {
+        let mut player = CurvePlayer::new(line(), 2.0).with_delay(1.0);
+
+        player.seek(1.0);
+        assert_close(player.progress(), 0.5);
+        assert!(!player.advance(0.1).started);
+
+        player.seek(5.0);
+        assert!(player.is_finished());
+
+        let mut looping = CurvePlayer::new(line(), 2.0).with_mode(PlayMode::Loop);
+        looping.seek(2.0);
+        assert!(!looping.is_finished() && looping.is_playing());
+    }
+
+    #[test]
+    fn samples_empty_curves() {
+        let mut player = CurvePlayer::new(Curve::default(), 1.0).with_range(Rangef::new(5.0, 6.0));
+        player.advance(0.5);
+        assert_eq!(player.value(), 5.0);
+    }
+
+    #[test]
+    fn drives_targets() {
+        let mut player = CurvePlayer::new(line(), 1.0)
+            .with_property("x", Rangef::new(0.0, 10.0))
+            .with_property("alpha", Rangef::new(1.0, 0.0));
+
+        let mut value = 0.0;
+        player.drive(0.5, &mut value);
+        assert_close(value, 0.5);
+
+        let mut target = alloc::collections::BTreeMap::new();
+        player.apply_properties(&mut target);
+        assert_close(target["x"], 5.0);
+        assert_close(target["alpha"], 0.5);
+    }
+}
@@ -0,0 +1,13 @@
+// Names the standard prelude provides, for building without it.
+
+// Unused when a dependency links `std` anyway, whose float methods then take precedence
+#[cfg(not(feature = "std"))]
+#[allow(unused_imports)]
+pub(crate) use crate::float::Float;
+pub(crate) use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
@@ -0,0 +1,197 @@
+// Anything that gives a value for a time in `0..=1`, so that callers can take a constant, a curve or
+// something in between through the same parameter.
+
+use crate::prelude::*;
+use crate::Curve;
+
+/// A value that varies over `0..=1`.
+pub trait Sampleable<T = f32> {
+    fn sample(&self, x: f32) -> T;
+}
+
+impl Sampleable<f32> for Curve {
+    fn sample(&self, x: f32) -> f32 {
+        Curve::sample(self, x)
+    }
+}
+
+impl<T, S: Sampleable<T> + ?Sized> Sampleable<T> for &S {
+    fn sample(&self, x: f32) -> T {
+        (**self).sample(x)
+    }
+}
+
+impl<T, S: Sampleable<T> + ?Sized> Sampleable<T> for Box<S> {
+    fn sample(&self, x: f32) -> T {
+        (**self).sample(x)
+    }
+}
+
+/// The same value everywhere.
+#[derive(Clone, Copy, Debug, Default, serde::Deserialize, serde::Serialize)]
+pub struct Constant<T>(pub T);
+
+impl<T: Clone> Sampleable<T> for Constant<T> {
+    fn sample(&self, _x: f32) -> T {
+        self.0.clone()
+    }
+}
+
+/// A closure as a [`Sampleable`], see [`from_fn`].
+#[derive(Clone, Copy)]
+pub struct FromFn<F>(pub F);
+
+impl<T, F: Fn(f32) -> T> Sampleable<T> for FromFn<F> {
+    fn sample(&self, x: f32) -> T {
+        (self.0)(x)
+    }
+}
+
+pub fn from_fn<T, F: Fn(f32) -> T>(f: F) -> FromFn<F> {
+    FromFn(f)
+}
+
+/// A fixed point between two bounds, picked once and followed over time.
+///
+/// Like particles that each get their own curve between a lower and an upper one.
+#[derive(Clone, Copy, Debug)]
+pub struct RandomRange<A, B> {
+    pub min: A,
+    pub max: B,
+    pub t: f32,
+}
+
+#[allow(unused)]
+impl<A: Sampleable<f32>, B: Sampleable<f32>> RandomRange<A, B> {
+    pub fn new(min: A, max: B, t: f32) -> Self {
+        Self { min, max, t }
+    }
+
+    #[cfg(feature = "rand")]
+    pub fn random<R: rand::Rng + ?Sized>(min: A, max: B, rng: &mut R) -> Self {
+        Self::new(min, max, rng.gen())
+    }
+}
+
+impl<A: Sampleable<f32>, B: Sampleable<f32>> Sampleable<f32> for RandomRange<A, B> {
+    fn sample(&self, x: f32) -> f32 {
+        let (min, max) = (self.min.sample(x), self.max.sample(x));
+
+        min + (max - min) * self.t
+    }
+}
+
+/// Evenly spaced samples of a curve, interpolated linearly.
+///
+/// Cheaper to sample than the curve, at the cost of memory and of detail between samples.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct BakedCurve {
+    values: Vec<f32>,
+}
+
+#[allow(unused)]
+impl BakedCurve {
+    /// Bakes `resolution + 1` samples.
+    pub fn new(curve: &impl Sampleable<f32>, resolution: usize) -> Self {
+        let resolution = resolution.max(1);
+
+        Self {
+            values: (0..=resolution)
+                .map(|i| curve.sample(i as f32 / resolution as f32))
+                .collect(),
+        }
+    }
+
+    pub fn values(&self) -> &[f32] {
+        &self.values
+    }
+}
+
+impl Sampleable<f32> for BakedCurve {
+    /// Gives `0.0` without values, which only a deserialized curve can lack.
+    ///
+    /// A non-finite `x` gives the first value, like [`Curve::sample`].
+    fn sample(&self, x: f32) -> f32 {
+        if !x.is_finite() {
+            return self.values.first().copied().unwrap_or(0.0);
+        }
+
+        let resolution = self.values.len().saturating_sub(1);
+        let position = x.clamp(0.0, 1.0) * resolution as f32;
+        let i = (position as usize).min(resolution.saturating_sub(1));
+        let t = position - i as f32;
+
+        match (self.values.get(i), self.values.get(i + 1)) {
+            (Some(a), Some(b)) => a + (b - a) * t,
+            (Some(a), None) => *a,
+            _ => 0.0,
+        }
+    }
+}
+
+#[allow(unused)]
+impl Curve {
+    pub fn bake(&self, resolution: usize) -> BakedCurve {
+        BakedCurve::new(self, resolution)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::pos2;
+    use crate::Point;
+
+    #[test]
+    fn samples_baked_values() {
+        let curve = Curve::from_points([
+            Point::from_pos(pos2(0.0, 0.0)),
+            Point::from_pos(pos2(1.0, 1.0)),
+        ]);
+        let baked = curve.bake(4);
+
+        assert_eq!(baked.values().len(), 5);
+        assert_eq!(baked.sample(0.0), 0.0);
+        assert_eq!(baked.sample(0.375), 0.375);
+        assert_eq!(baked.sample(1.0), 1.0);
+        assert_eq!(baked.sample(-1.0), 0.0);
+        assert_eq!(baked.sample(2.0), 1.0);
+        assert_eq!(BakedCurve::new(&curve, 0).values().len(), 2);
+    }
+
+    #[test]
+    fn samples_non_finite_like_curve() {
+        let curve = Curve::from_points([
+            Point::from_pos(pos2(0.0, 0.25)),
+            Point::from_pos(pos2(1.0, 0.75)),
+        ]);
+        let baked = curve.bake(8);
+
+        for x in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            assert_eq!(baked.sample(x), 0.25);
+            assert_eq!(baked.sample(x), curve.sample(x));
+        }
+        assert_eq!(BakedCurve { values: vec![] }.sample(f32::NAN), 0.0);
+        assert_eq!(Curve::default().sample(f32::NAN), 0.0);
+    }
+
+    #[test]
+    fn samples_deserialized_values() {
+        let empty = BakedCurve { values: vec![] };
+        assert_eq!(empty.sample(0.5), 0.0);
+
+        let single = BakedCurve { values: vec![0.25] };
+        assert_eq!(single.sample(0.0), 0.25);
+        assert_eq!(single.sample(1.0), 0.25);
+    }
+
+    #[test]
+    fn samples_constants_and_ranges() {
+        assert_eq!(Constant(3).sample(0.5), 3);
+        assert_eq!(from_fn(|x| x * 2.0).sample(0.25), 0.5);
+
+        let range = RandomRange::new(Constant(1.0), from_fn(|x| 1.0 + x), 0.5);
+        assert_eq!(range.sample(0.0), 1.0);
+        assert_eq!(range.sample(1.0), 1.5);
+    }
+}
@@ -0,0 +1,166 @@
+// Key reduction, greedily removing the point whose removal changes the curve the least.
+//
+// The two segments around a removed point are merged and their outer tangents refit against the
+// original curve, so that errors never pile up over successive removals.
+
+use crate::fit::{fit_tangents, max_error};
+use crate::math::{Float, Pos2};
+use crate::prelude::*;
+use crate::CurveT;
+
+#[allow(unused)]
+impl<S: Float> CurveT<S> {
+    /// Removes points while the curve stays within `tolerance` in y, returns how many were removed.
+    pub fn simplify(&mut self, tolerance: S) -> usize {
+        let last = self.points().len().saturating_sub(1);
+
+        self.simplify_range(0, last, tolerance)
+    }
+
+    /// Like [`CurveT::simplify`], only removing points strictly between `first` and `last`.
+    pub fn simplify_range(&mut self, first: usize, last: usize, tolerance: S) -> usize {
+        let original = self.clone();
+        let mut last = last.min(self.points().len().saturating_sub(1));
+
+        let mut removed = 0;
+        while last > first + 1 {
+            // Cheapest point to remove, with the tangents of the merged segment
+            let best = (first + 1..last)
+                .filter_map(|index| {
+                    let samples = original_samples(&original, self, index - 1, index + 1)?;
+                    let (left_tan, right_tan) = fit_tangents(&samples, None, None);
+                    let (error, _) = max_error(&samples, left_tan, right_tan);
+
+                    Some((index, error, left_tan, right_tan))
+                })
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+
+            let Some((index, error, left_tan, right_tan)) = best else {
+                break;
+            };
+            if error > tolerance {
+                break;
+            }
+
+            let _ = self.try_remove_point(index);
+            self.set_right_tan(index - 1, left_tan);
+            self.set_left_tan(index, right_tan);
+
+            last -= 1;
+            removed += 1;
+        }
+
+        removed
+    }
+}
+
+/// Samples of the original curve between two points of the simplified one.
+fn original_samples<S: Float>(
+    original: &CurveT<S>,
+    simplified: &CurveT<S>,
+    start: usize,
+    end: usize,
+) -> Option<Vec<Pos2<S>>> {
+    const RESOLUTION: usize = 64;
+
+    let a = simplified.get_position(start)?;
+    let b = simplified.get_position(end)?;
+    if b.x - a.x < S::TOLERANCE {
+        return None;
+    }
+
+    // Even samples plus every original point, so that no detail falls between samples
+    let mut xs: Vec<S> = (1..RESOLUTION)
+        .map(|i| a.x + (b.x - a.x) * S::from_f32(i as f32 / RESOLUTION as f32))
+        .chain(
+            original
+                .points()
+                .iter()
+                .map(|point| point.pos().x)
+                .filter(|&x| x > a.x && x < b.x),
+        )
+        .collect();
+    xs.sort_by(S::total_cmp);
+
+    let mut samples = vec![a];
+    samples.extend(xs.into_iter().map(|x| Pos2::new(x, original.sample(x))));
+    samples.push(b);
+
+    Some(samples)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::pos2;
+    use crate::{Curve, Point};
+
+    fn sampled(f: impl Fn(f32) -> f32, count: usize) -> Curve {
+        Curve::from_points((0..=count).map(|i| {
+            let x = i as f32 / count as f32;
+            Point::from_pos(pos2(x, f(x)))
+        }))
+    }
+
+    fn max_distance(a: &Curve, b: &Curve) -> f32 {
+        (0..=200)
+            .map(|i| {
+                let x = i as f32 / 200.0;
+                (a.sample(x) - b.sample(x)).abs()
+            })
+            .fold(0.0, f32::max)
+    }
+
+    #[test]
+    fn removes_collinear_points() {
+        let mut curve = sampled(|x| 0.1 + 0.8 * x, 10);
+        let original = curve.clone();
+
+        assert_eq!(curve.simplify(0.0001), 9);
+        assert_eq!(curve.get_position(0), original.get_position(0));
+        assert_eq!(curve.get_position(1), original.get_position(10));
+        assert!(max_distance(&curve, &original) < 0.0001);
+    }
+
+    #[test]
+    fn stays_within_tolerance() {
+        let original = sampled(|x| 0.5 + 0.4 * (x * 9.0).sin(), 60);
+
+        for tolerance in [0.05, 0.01, 0.001] {
+            let mut curve = original.clone();
+            let removed = curve.simplify(tolerance);
+
+            assert!(removed > 0);
+            assert_eq!(curve.points().len(), original.points().len() - removed);
+            assert!(max_distance(&curve, &original) <= tolerance + 0.001);
+        }
+    }
+
+    #[test]
+    fn keeps_points_outside_range() {
+        let original = sampled(|x| x * x, 10);
+        let mut curve = original.clone();
+
+        // Only points 3 to 7 can go
+        let removed = curve.simplify_range(2, 8, 0.01);
+        assert!(removed > 0 && removed <= 5);
+        for index in 0..=2 {
+            assert_eq!(curve.get_position(index), original.get_position(index));
+        }
+        assert_eq!(curve.get_position(8 - removed), original.get_position(8));
+        assert_eq!(curve.get_position(10 - removed), original.get_position(10));
+
+        // Ranges without inner points, or past the end, remove nothing or stop at the end
+        assert_eq!(curve.clone().simplify_range(4, 5, 1.0), 0);
+        assert_eq!(curve.clone().simplify_range(9, 100, 1.0), 0);
+    }
+
+    #[test]
+    fn simplifies_small_curves() {
+        assert_eq!(Curve::default().simplify(1.0), 0);
+        assert_eq!(sampled(|_| 0.5, 0).simplify(1.0), 0);
+        assert_eq!(sampled(|x| x, 1).simplify(1.0), 0);
+
+        // Nothing is removed with a negative tolerance
+        assert_eq!(sampled(|x| x, 5).simplify(-1.0), 0);
+    }
+}
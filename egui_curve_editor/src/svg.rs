@@ -0,0 +1,366 @@
+// SVG paths, with y pointing down and the curve spanning a `width` by `height` box.
+
+use crate::curve::bezier_to_points;
+use crate::math::{pos2, Pos2, Vec2};
+use crate::prelude::*;
+use crate::{Curve, ParseError, Point, TangentMode};
+
+#[allow(unused)]
+impl Curve {
+    /// Writes the curve as path data made of one cubic `C` command per segment.
+    ///
+    /// The curve is held flat from `x = 0` to its first point and from its last point to `x = 1`.
+    pub fn to_svg_path(&self, width: f32, height: f32) -> String {
+        let to_svg = |pos: Pos2| {
+            format!(
+                "{} {}",
+                format_number(pos.x * width),
+                format_number((1.0 - pos.y) * height)
+            )
+        };
+
+        let points = self.points();
+        let (Some(first), Some(last)) = (points.first(), points.last()) else {
+            return String::new();
+        };
+
+        let mut path = format!("M {}", to_svg(pos2(0.0, first.pos().y)));
+        if first.pos().x > 0.0 {
+            path += &format!(" L {}", to_svg(first.pos()));
+        }
+        for index in 0..points.len() - 1 {
+            let [_, control_1, control_2, end] = self.segment_bezier(index).unwrap();
+            path += &format!(
+                " C {} {} {}",
+                to_svg(control_1),
+                to_svg(control_2),
+                to_svg(end)
+            );
+        }
+        if last.pos().x < 1.0 {
+            path += &format!(" L {}", to_svg(pos2(1.0, last.pos().y)));
+        }
+
+        path
+    }
+
+    /// Writes a standalone SVG document showing the curve over a grid.
+    pub fn to_svg_document(&self, width: f32, height: f32) -> String {
+        const DIVISIONS: usize = 10;
+
+        let curve = self.to_svg_path(width, height);
+
+        let mut grid = String::new();
+        for i in 1..DIVISIONS {
+            let x = format_number(width * i as f32 / DIVISIONS as f32);
+            let y = format_number(height * i as f32 / DIVISIONS as f32);
+            grid += &format!(
+                "M {x} 0 V {} M 0 {y} H {} ",
+                format_number(height),
+                format_number(width)
+            );
+        }
+
+        let (width, height) = (format_number(width), format_number(height));
+        format!(
+            concat!(
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">\n",
+                "  <rect width=\"{w}\" height=\"{h}\" fill=\"white\"/>\n",
+                "  <path d=\"{grid}\" fill=\"none\" stroke=\"#dddddd\" stroke-width=\"1\"/>\n",
+                "  <path d=\"M 0 0 V {h} H {w}\" fill=\"none\" stroke=\"#000000\" stroke-width=\"1.5\"/>\n",
+                "  <path d=\"{curve}\" fill=\"none\" stroke=\"#1e78c8\" stroke-width=\"2\"/>\n",
+                "</svg>\n",
+            ),
+            w = width,
+            h = height,
+            grid = grid.trim_end(),
+            curve = curve,
+        )
+    }
+
+    /// Reads path data made of a single subpath of `M`, `L`, `H`, `V` and `C` commands.
+    ///
+    /// Pairs of numbers following the move are lines, as in SVG. The path must move forward in x
+    /// so that it is a function.
+    pub fn from_svg_path(path: &str, width: f32, height: f32) -> Result<Self, ParseError> {
+        const TOLERANCE: f32 = 0.001;
+
+        if width <= 0.0 || height <= 0.0 {
+            return Err(ParseError::new("the path box must not be empty"));
+        }
+
+        let tokens = tokenize(path)?;
+        let to_curve = |pos: Pos2| pos2(pos.x / width, 1.0 - pos.y / height);
+
+        let mut points: Vec<Point> = vec![];
+        let mut current = Pos2::ZERO;
+        let mut repeated = None;
+        let mut i = 0;
+        while i < tokens.len() {
+            // Commands repeat implicitly while numbers follow
+            if let Token::Command(next) = tokens[i] {
+                repeated = Some(next);
+                i += 1;
+            }
+            let Some(command) = repeated else {
+                return Err(ParseError::new("path data must start with a command"));
+            };
+
+            let arguments = match command.to_ascii_uppercase() {
+                'M' | 'L' => 2,
+                'H' | 'V' => 1,
+                'C' => 6,
+                _ => return Err(ParseError::new(format!("unsupported command `{command}`"))),
+            };
+            let numbers = tokens
+                .get(i..i + arguments)
+                .and_then(|tokens| {
+                    tokens
+                        .iter()
+                        .map(|token| match token {
+                            Token::Number(number) => Some(*number),
+                            Token::Command(_) => None,
+                        })
+                        .collect::<Option<Vec<f32>>>()
+                })
+                .ok_or_else(|| ParseError::new(format!("`{command}` takes {arguments} numbers")))?;
+            i += arguments;
+
+            let origin = if command.is_ascii_lowercase() {
+                current.to_vec2()
+            } else {
+                Vec2::ZERO
+            };
+            let absolute = |x: f32, y: f32| pos2(x, y) + origin;
+
+            let start = to_curve(current);
+            match command.to_ascii_uppercase() {
+                'M' => {
+                    if !points.is_empty() {
+                        return Err(ParseError::new("only a single subpath is supported"));
+                    }
+                    current = absolute(numbers[0], numbers[1]);
+                    points.push(Point::from_pos(to_curve(current)));
+
+                    // Pairs after a move are lines
+                    repeated = Some(if command == 'm' { 'l' } else { 'L' });
+                    continue;
+                }
+                'L' => current = absolute(numbers[0], numbers[1]),
+                'H' => current.x = numbers[0] + origin.x,
+                'V' => current.y = numbers[0] + origin.y,
+                _ => {}
+            }
+
+            let Some(last) = points.last_mut() else {
+                return Err(ParseError::new("path data must start with `M`"));
+            };
+
+            if command.eq_ignore_ascii_case(&'C') {
+                let bezier = [
+                    start,
+                    to_curve(absolute(numbers[0], numbers[1])),
+                    to_curve(absolute(numbers[2], numbers[3])),
+                    to_curve(absolute(numbers[4], numbers[5])),
+                ];
+                if bezier.windows(2).any(|pair| pair[1].x < pair[0].x) {
+                    return Err(ParseError::new("the path goes back in x"));
+                }
+                current = absolute(numbers[4], numbers[5]);
+
+                let keys = bezier_to_points(bezier, TOLERANCE);
+                *last = Point::new(
+                    last.pos(),
+                    last.left_tan(),
+                    keys[0].right_tan(),
+                    last.left_mode(),
+                    TangentMode::Free,
+                );
+                points.extend_from_slice(&keys[1..]);
+            } else {
+                // Straight lines keep linear tangents
+                let end = to_curve(current);
+                if end.x < start.x {
+                    return Err(ParseError::new("the path goes back in x"));
+                }
+                *last = Point::new(
+                    last.pos(),
+                    last.left_tan(),
+                    0.0,
+                    last.left_mode(),
+                    TangentMode::Linear,
+                );
+                points.push(Point::new(
+                    end,
+                    0.0,
+                    0.0,
+                    TangentMode::Linear,
+                    TangentMode::Linear,
+                ));
+            }
+        }
+
+        Ok(Curve::from_points(points))
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Token {
+    Command(char),
+    Number(f32),
+}
+
+fn tokenize(path: &str) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = vec![];
+    let mut chars = path.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() || c == ',' {
+            chars.next();
+        } else if c.is_ascii_alphabetic() && c != 'e' && c != 'E' {
+            tokens.push(Token::Command(c));
+            chars.next();
+        } else {
+            // Numbers end at a second sign or point, which starts the next number
+            let mut end = start;
+            let mut seen_point = false;
+            let mut seen_exponent = false;
+            let mut previous = None;
+            while let Some(&(i, c)) = chars.peek() {
+                let accepted = match c {
+                    '0'..='9' => true,
+                    '+' | '-' => i == start || matches!(previous, Some('e' | 'E')),
+                    '.' if !seen_point && !seen_exponent => {
+                        seen_point = true;
+                        true
+                    }
+                    'e' | 'E' if !seen_exponent => {
+                        seen_exponent = true;
+                        true
+                    }
+                    _ => false,
+                };
+                if !accepted {
+                    break;
+                }
+
+                previous = Some(c);
+                end = i + c.len_utf8();
+                chars.next();
+            }
+
+            let text = &path[start..end];
+            let number = text
+                .parse()
+                .map_err(|_| ParseError::new(format!("invalid number `{}`", &path[start..])))?;
+            tokens.push(Token::Number(number));
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn format_number(value: f32) -> String {
+    let text = format!("{:.3}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+
+    if text == "-0" {
+        "0".to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_samples(curve: &Curve, expected: &[(f32, f32)]) {
+        for &(x, y) in expected {
+            let value = curve.sample(x);
+            assert!(
+                (value - y).abs() < 0.002,
+                "sample({x}) = {value}, expected {y}"
+            );
+        }
+    }
+
+    fn positions(curve: &Curve) -> Vec<Pos2> {
+        curve.points().iter().map(|point| point.pos()).collect()
+    }
+
+    #[test]
+    fn reads_lines() {
+        let curve = Curve::from_svg_path("M 0 100 L 50 50 L 100 0", 100.0, 100.0).unwrap();
+        assert_eq!(curve.points().len(), 3);
+        assert_samples(
+            &curve,
+            &[(0.0, 0.0), (0.25, 0.25), (0.75, 0.75), (1.0, 1.0)],
+        );
+    }
+
+    #[test]
+    fn reads_implicit_lines_after_move() {
+        let absolute = Curve::from_svg_path("M 0 100 50 50 100 0", 100.0, 100.0).unwrap();
+        assert_eq!(absolute.points().len(), 3);
+        assert_samples(&absolute, &[(0.25, 0.25), (0.5, 0.5), (1.0, 1.0)]);
+
+        let relative = Curve::from_svg_path("m 0 100 50 -50 50 -50", 100.0, 100.0).unwrap();
+        assert_eq!(positions(&relative), positions(&absolute));
+    }
+
+    #[test]
+    fn reads_horizontal_and_vertical_lines() {
+        let curve = Curve::from_svg_path("M 0 100 H 50 V 0 H 100", 100.0, 100.0).unwrap();
+        assert_samples(&curve, &[(0.25, 0.0), (0.75, 1.0)]);
+
+        let relative = Curve::from_svg_path("M 0 100 h 50 v -100 h 50", 100.0, 100.0).unwrap();
+        assert_eq!(positions(&relative), positions(&curve));
+    }
+
+    #[test]
+    fn reads_cubics() {
+        let curve = Curve::from_svg_path("M0,100C50,100,50,0,100,0", 100.0, 100.0).unwrap();
+        assert_samples(&curve, &[(0.0, 0.0), (0.5, 0.5), (1.0, 1.0)]);
+        assert!(curve.sample(0.2) < 0.2);
+
+        // Repeated and relative
+        let curve = Curve::from_svg_path(
+            "M 0 100 c 25 0 25 -50 50 -50 25 0 25 -50 50 -50",
+            100.0,
+            100.0,
+        )
+        .unwrap();
+        assert_samples(&curve, &[(0.5, 0.5), (1.0, 1.0)]);
+    }
+
+    #[test]
+    fn round_trips_paths() {
+        let curve = Curve::from_svg_path("M 0 80 C 20 80 30 0 100 20", 100.0, 80.0).unwrap();
+        let path = curve.to_svg_path(100.0, 80.0);
+        let read = Curve::from_svg_path(&path, 100.0, 80.0).unwrap();
+
+        for i in 0..=20 {
+            let x = i as f32 / 20.0;
+            assert!((read.sample(x) - curve.sample(x)).abs() < 0.01);
+        }
+        assert!(curve.to_svg_document(100.0, 80.0).starts_with("<svg"));
+        assert_eq!(Curve::default().to_svg_path(100.0, 100.0), "");
+    }
+
+    #[test]
+    fn rejects_invalid_paths() {
+        for path in [
+            "0 0 L 1 1",
+            "L 10 10",
+            "M 0 0 L 10",
+            "M 0 0 Q 1 1 2 2",
+            "M 0 0 L 10 10 M 20 20",
+            "M 50 0 L 10 10",
+            "M 0 0 C 50 0 -10 0 100 0",
+            "M 0 0 L 1.2.3.4x 1",
+        ] {
+            assert!(Curve::from_svg_path(path, 100.0, 100.0).is_err(), "{path}");
+        }
+        assert!(Curve::from_svg_path("M 0 0", 0.0, 100.0).is_err());
+    }
+}
@@ -0,0 +1,311 @@
+// Curves over time, for animation, where `0..=1` spans a duration in seconds split into frames.
+
+use core::time::Duration;
+
+use crate::math::pos2;
+use crate::prelude::*;
+use crate::{Curve, ParseError, Point};
+
+/// How times are labeled, see [`Timeline::label`].
+#[derive(PartialEq, Clone, Copy, Default, serde::Deserialize, serde::Serialize)]
+pub enum TimeDisplay {
+    #[default]
+    Frames,
+    /// `minutes:seconds:frames`, with minutes counting past an hour rather than adding hours.
+    Timecode,
+}
+
+/// A duration at a frame rate, mapping curve offsets to seconds and frames.
+///
+/// Deserializing fails on durations and frame rates that are not positive and finite.
+#[derive(PartialEq, Clone, Copy, serde::Deserialize, serde::Serialize)]
+#[serde(try_from = "TimelineData")]
+pub struct Timeline {
+    duration: f32,
+    frame_rate: f32,
+    #[serde(default)]
+    display: TimeDisplay,
+}
+
+/// The serialized fields of a [`Timeline`], checked before use.
+#[derive(serde::Deserialize)]
+struct TimelineData {
+    duration: f32,
+    frame_rate: f32,
+    #[serde(default)]
+    display: TimeDisplay,
+}
+
+impl TryFrom<TimelineData> for Timeline {
+    type Error = ParseError;
+
+    fn try_from(data: TimelineData) -> Result<Self, ParseError> {
+        let positive = |value: f32| value.is_finite() && value > 0.0;
+        if !positive(data.duration) {
+            return Err(ParseError::new(format!(
+                "invalid duration `{}`, it must be positive",
+                data.duration
+            )));
+        }
+        if !positive(data.frame_rate) {
+            return Err(ParseError::new(format!(
+                "invalid frame rate `{}`, it must be positive",
+                data.frame_rate
+            )));
+        }
+
+        Ok(Self::new(data.duration, data.frame_rate).with_display(data.display))
+    }
+}
+
+impl Default for Timeline {
+    fn default() -> Self {
+        Self::new(1.0, 30.0)
+    }
+}
+
+#[allow(unused)]
+impl Timeline {
+    /// Durations and frame rates are kept positive.
+    pub fn new(duration: f32, frame_rate: f32) -> Self {
+        Self {
+            duration: duration.max(f32::EPSILON),
+            frame_rate: frame_rate.max(f32::EPSILON),
+            display: TimeDisplay::default(),
+        }
+    }
+
+    pub fn with_display(self, display: TimeDisplay) -> Self {
+        Self { display, ..self }
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    pub fn frame_rate(&self) -> f32 {
+        self.frame_rate
+    }
+
+    pub fn display(&self) -> TimeDisplay {
+        self.display
+    }
+
+    /// Index of the last frame, the first one is `0` at offset `0`.
+    pub fn last_frame(&self) -> usize {
+        (self.duration * self.frame_rate).round() as usize
+    }
+
+    pub fn seconds_at(&self, offset: f32) -> f32 {
+        offset * self.duration
+    }
+
+    pub fn offset_at_seconds(&self, seconds: f32) -> f32 {
+        seconds / self.duration
+    }
+
+    pub fn offset_at_frame(&self, frame: usize) -> f32 {
+        self.offset_at_seconds(frame as f32 / self.frame_rate)
+    }
+
+    /// The nearest frame, within the timeline.
+    pub fn frame_at(&self, offset: f32) -> usize {
+        let frame = (self.seconds_at(offset) * self.frame_rate).round();
+
+        (frame.max(0.0) as usize).min(self.last_frame())
+    }
+
+    /// Moves an offset to the nearest frame.
+    pub fn snap(&self, offset: f32) -> f32 {
+        self.offset_at_frame(self.frame_at(offset)).min(1.0)
+    }
+
+    /// The frame as a number or as a timecode, depending on [`Timeline::display`].
+    pub fn label(&self, frame: usize) -> String {
+        match self.display {
+            TimeDisplay::Frames => frame.to_string(),
+            TimeDisplay::Timecode => {
+                let frames_per_second = (self.frame_rate.round() as usize).max(1);
+                let seconds = frame / frames_per_second;
+
+                format!(
+                    "{:02}:{:02}:{:02}",
+                    seconds / 60,
+                    seconds % 60,
+                    frame % frames_per_second
+                )
+            }
+        }
+    }
+}
+
+/// A named curve, one row of a dope sheet.
+#[derive(Clone, Default, serde::Deserialize, serde::Serialize)]
+pub struct Track {
+    pub name: String,
+    pub curve: Curve,
+}
+
+impl Track {
+    pub fn new(name: impl Into<String>, curve: Curve) -> Self {
+        Self {
+            name: name.into(),
+            curve,
+        }
+    }
+}
+
+/// A [`Curve`] on a [`Timeline`], sampled in seconds or frames.
+#[derive(Clone, Default, serde::Deserialize, serde::Serialize)]
+pub struct KeyframeCurve {
+    pub curve: Curve,
+    pub timeline: Timeline,
+}
+
+#[allow(unused)]
+impl KeyframeCurve {
+    pub fn new(curve: Curve, timeline: Timeline) -> Self {
+        Self { curve, timeline }
+    }
+
+    pub fn sample_seconds(&self, seconds: f32) -> f32 {
+        self.curve.sample(self.timeline.offset_at_seconds(seconds))
+    }
+
+    pub fn sample_duration(&self, time: Duration) -> f32 {
+        self.sample_seconds(time.as_secs_f32())
+    }
+
+    pub fn sample_frame(&self, frame: usize) -> f32 {
+        self.curve.sample(self.timeline.offset_at_frame(frame))
+    }
+
+    /// Times of the keys, in seconds.
+    pub fn key_times(&self) -> Vec<f32> {
+        self.curve
+            .points()
+            .iter()
+            .map(|point| self.timeline.seconds_at(point.pos().x))
+            .collect()
+    }
+
+    /// Moves every key to its nearest frame, keys landing on the same frame are all kept.
+    pub fn snap_keys(&mut self) {
+        let monotone = self.curve.monotone_constraint();
+
+        self.curve = Curve::from_points(self.curve.points().iter().map(|point| {
+            let pos = pos2(self.timeline.snap(point.pos().x), point.pos().y);
+
+            Point::new(
+                pos,
+                point.left_tan(),
+                point.right_tan(),
+                point.left_mode(),
+                point.right_mode(),
+            )
+        }));
+        self.curve.set_monotone_constraint(monotone);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_offsets_to_frames() {
+        let timeline = Timeline::new(2.0, 30.0);
+
+        assert_eq!(timeline.last_frame(), 60);
+        assert_eq!(timeline.seconds_at(0.5), 1.0);
+        assert_eq!(timeline.offset_at_frame(15), 0.25);
+        assert_eq!(timeline.frame_at(0.25), 15);
+        assert_eq!(timeline.frame_at(-1.0), 0);
+        assert_eq!(timeline.frame_at(2.0), 60);
+        assert_eq!(timeline.frame_at(f32::NAN), 0);
+        assert_eq!(timeline.snap(0.255), 0.25);
+        assert_eq!(timeline.snap(1.0), 1.0);
+    }
+
+    #[test]
+    fn keeps_durations_positive() {
+        let timeline = Timeline::new(0.0, -24.0);
+        assert!(timeline.duration() > 0.0);
+        assert!(timeline.frame_rate() > 0.0);
+
+        let timeline = Timeline::new(f32::NAN, f32::NAN);
+        assert!(timeline.duration() > 0.0);
+        assert_eq!(timeline.last_frame(), 0);
+    }
+
+    #[test]
+    fn labels_frames() {
+        let timeline = Timeline::new(10.0, 24.0);
+        assert_eq!(timeline.label(30), "30");
+
+        let timeline = timeline.with_display(TimeDisplay::Timecode);
+        assert_eq!(timeline.label(0), "00:00:00");
+        assert_eq!(timeline.label(30), "00:01:06");
+        assert_eq!(timeline.label(24 * 61), "01:01:00");
+        assert_eq!(timeline.label(24 * 3600), "60:00:00");
+
+        // Fractional rates count whole frames per second
+        let timeline = Timeline::new(1.0, 29.97).with_display(TimeDisplay::Timecode);
+        assert_eq!(timeline.label(31), "00:01:01");
+    }
+
+    #[test]
+    fn deserializes_checked_timelines() {
+        let timeline: Timeline =
+            serde_json::from_str(r#"{"duration": 2.0, "frame_rate": 24.0, "display": "Timecode"}"#)
+                .unwrap();
+        assert!(timeline == Timeline::new(2.0, 24.0).with_display(TimeDisplay::Timecode));
+
+        let timeline: Timeline =
+            serde_json::from_str(&serde_json::to_string(&Timeline::default()).unwrap()).unwrap();
+        assert!(timeline == Timeline::default());
+
+        for text in [
+            r#"{"duration": 0.0, "frame_rate": 24.0}"#,
+            r#"{"duration": -1.0, "frame_rate": 24.0}"#,
+            r#"{"duration": 1.0, "frame_rate": 0.0}"#,
+        ] {
+            assert!(serde_json::from_str::<Timeline>(text).is_err(), "{text}");
+        }
+        let error = ron::from_str::<Timeline>("(duration: inf, frame_rate: 24.0)")
+            .err()
+            .unwrap();
+        assert!(error.to_string().contains("invalid duration"));
+    }
+
+    #[test]
+    fn samples_keyframes() {
+        let curve = Curve::from_points([
+            Point::from_pos(pos2(0.0, 0.0)),
+            Point::from_pos(pos2(1.0, 1.0)),
+        ]);
+        let keyframes = KeyframeCurve::new(curve, Timeline::new(4.0, 10.0));
+
+        assert!((keyframes.sample_seconds(1.0) - 0.25).abs() < 0.0001);
+        assert!((keyframes.sample_duration(Duration::from_secs(2)) - 0.5).abs() < 0.0001);
+        assert!((keyframes.sample_frame(30) - 0.75).abs() < 0.0001);
+        assert_eq!(keyframes.sample_seconds(10.0), 1.0);
+        assert_eq!(keyframes.key_times(), vec![0.0, 4.0]);
+    }
+
+    #[test]
+    fn snaps_keys_to_frames() {
+        let curve = Curve::from_points([
+            Point::from_pos(pos2(0.0, 0.0)),
+            Point::from_pos(pos2(0.26, 0.5)),
+            Point::from_pos(pos2(0.74, 0.5)),
+            Point::from_pos(pos2(1.0, 1.0)),
+        ]);
+        let mut keyframes = KeyframeCurve::new(curve, Timeline::new(1.0, 4.0));
+        keyframes.curve.set_monotone_constraint(true);
+        keyframes.snap_keys();
+
+        assert_eq!(keyframes.key_times(), vec![0.0, 0.25, 0.75, 1.0]);
+        assert!(keyframes.curve.monotone_constraint());
+    }
+}
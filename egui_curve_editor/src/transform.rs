@@ -0,0 +1,363 @@
+// Affine transformations of points, keeping the shape of the segments between them.
+//
+// Scaling x by `sx` and y by `sy` scales slopes by `sy / sx`. A negative x scale mirrors time, which
+// also swaps the sides of every tangent.
+
+use crate::math::{Float, Pos2, Rangef, Vec2};
+use crate::prelude::*;
+use crate::{CurveT, PointT};
+
+#[allow(unused)]
+impl<S: Float> CurveT<S> {
+    /// Maps every `y` to `1 - y`.
+    pub fn flip_vertical(&mut self) {
+        let half = S::from_f32(0.5);
+        self.scale(Vec2::new(S::ONE, -S::ONE), Pos2::new(half, half));
+    }
+
+    /// Plays the curve backwards, mapping every `x` to `1 - x`.
+    pub fn reverse(&mut self) {
+        let half = S::from_f32(0.5);
+        self.scale(Vec2::new(-S::ONE, S::ONE), Pos2::new(half, half));
+    }
+
+    /// Scales every point away from `pivot`, a zero x scale leaves the curve unchanged.
+    pub fn scale(&mut self, scale: impl Into<Vec2<S>>, pivot: impl Into<Pos2<S>>) {
+        let indices: Vec<usize> = (0..self.points().len()).collect();
+        self.transform_points(&indices, scale.into(), pivot.into(), Vec2::ZERO);
+    }
+
+    pub fn offset(&mut self, offset: impl Into<Vec2<S>>) {
+        let indices: Vec<usize> = (0..self.points().len()).collect();
+        self.transform_points(&indices, Vec2::splat(S::ONE), Pos2::ZERO, offset.into());
+    }
+
+    /// Moves the points at `indices` in time, keeping their values and tangents.
+    ///
+    /// Returns the new indices of the moved points, see [`CurveT::transform_points`].
+    pub fn retime_points(&mut self, indices: &[usize], delta: S) -> Vec<usize> {
+        self.transform_points(
+            indices,
+            Vec2::splat(S::ONE),
+            Pos2::ZERO,
+            Vec2::new(delta, S::ZERO),
+        )
+    }
+
+    /// Maps the values the curve takes, overshoot included, to `target`.
+    pub fn normalize(&mut self, target: impl Into<Rangef<S>>) {
+        let Some(range) = self.value_range() else {
+            return;
+        };
+
+        let indices: Vec<usize> = (0..self.points().len()).collect();
+        self.remap_values(&indices, range, target.into());
+    }
+
+    /// Scales the points at `indices` away from `pivot`, then moves them by `offset`.
+    ///
+    /// The scale and offset are limited on each axis so that the moved points stay within `0..=1`
+    /// instead of being clamped onto its edges, where they would pile up. Points are sorted again,
+    /// returns the new indices of the moved points.
+    pub fn transform_points(
+        &mut self,
+        indices: &[usize],
+        scale: Vec2<S>,
+        pivot: Pos2<S>,
+        offset: Vec2<S>,
+    ) -> Vec<usize> {
+        if scale.x == S::ZERO || !scale.is_finite() || !pivot.is_finite() || !offset.is_finite() {
+            return indices.to_vec();
+        }
+
+        let mut x_range = Rangef::<S>::NOTHING;
+        let mut y_range = Rangef::<S>::NOTHING;
+        for &index in indices {
+            if let Some(pos) = self.get_position(index) {
+                x_range.min = x_range.min.min(pos.x);
+                x_range.max = x_range.max.max(pos.x);
+                y_range.min = y_range.min.min(pos.y);
+                y_range.max = y_range.max.max(pos.y);
+            }
+        }
+        if x_range.min > x_range.max {
+            return indices.to_vec();
+        }
+
+        let (scale_x, offset_x) = limit_to_unit(x_range, scale.x, pivot.x, offset.x);
+        let (scale_y, offset_y) = limit_to_unit(y_range, scale.y, pivot.y, offset.y);
+        let (scale, offset) = (Vec2::new(scale_x, scale_y), Vec2::new(offset_x, offset_y));
+        if scale.x == S::ZERO {
+            return indices.to_vec();
+        }
+
+        let slope = scale.y / scale.x;
+        let mut kept = vec![];
+        let mut moved = vec![];
+        for (index, point) in self.points().iter().enumerate() {
+            if !indices.contains(&index) {
+                kept.push((*point, false));
+                continue;
+            }
+
+            let pos = pivot + (point.pos() - pivot) * scale + offset;
+            let moved_point = if scale.x < S::ZERO {
+                PointT::new(
+                    pos,
+                    point.right_tan() * slope,
+                    point.left_tan() * slope,
+                    point.right_mode(),
+                    point.left_mode(),
+                )
+            } else {
+                PointT::new(
+                    pos,
+                    point.left_tan() * slope,
+                    point.right_tan() * slope,
+                    point.left_mode(),
+                    point.right_mode(),
+                )
+            };
+            moved.push((moved_point, true));
+        }
+
+        // Mirrored points come in the opposite order, so that jumps between them turn around too
+        if scale.x < S::ZERO {
+            moved.reverse();
+        }
+
+        self.replace_points(kept.into_iter().chain(moved).collect())
+    }
+
+    /// Maps the values of the points at `indices` to `target`, returns their new indices.
+    pub fn normalize_points(
+        &mut self,
+        indices: &[usize],
+        target: impl Into<Rangef<S>>,
+    ) -> Vec<usize> {
+        let mut range = Rangef::<S>::NOTHING;
+        for &index in indices {
+            if let Some(pos) = self.get_position(index) {
+                range.min = range.min.min(pos.y);
+                range.max = range.max.max(pos.y);
+            }
+        }
+        if range.min > range.max {
+            return indices.to_vec();
+        }
+
+        self.remap_values(indices, range, target.into())
+    }
+
+    fn remap_values(
+        &mut self,
+        indices: &[usize],
+        range: Rangef<S>,
+        target: Rangef<S>,
+    ) -> Vec<usize> {
+        // Flat values are moved to the middle of the target
+        if range.span() <= S::ZERO {
+            let offset = Vec2::new(S::ZERO, target.center() - range.min);
+            return self.transform_points(indices, Vec2::splat(S::ONE), Pos2::ZERO, offset);
+        }
+
+        let scale = Vec2::new(S::ONE, target.span() / range.span());
+        let offset = Vec2::new(S::ZERO, target.min - range.min);
+        self.transform_points(indices, scale, Pos2::new(S::ZERO, range.min), offset)
+    }
+
+    /// Rebuilds the curve from points tagged with whether their new index is wanted.
+    fn replace_points(&mut self, mut points: Vec<(PointT<S>, bool)>) -> Vec<usize> {
+        points.sort_by(|a, b| a.0.pos().x.total_cmp(&b.0.pos().x));
+
+        let indices = points
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, wanted))| *wanted)
+            .map(|(index, _)| index)
+            .collect();
+
+        let monotone = self.monotone_constraint();
+        *self = CurveT::from_points(points.into_iter().map(|(point, _)| point));
+        self.set_monotone_constraint(monotone);
+
+        indices
+    }
+}
+
+/// Limits the scale, then the offset, of values in `range` so that they stay within `0..=1`.
+///
+/// Transforms that already fit are kept. Otherwise, the scales keeping `range` within `0..=1` form an
+/// interval containing `1` and the scale is moved to its closest end, which can turn a mirroring into
+/// a flattening when the pivot is too far off-center for the mirrored values to fit.
+fn limit_to_unit<S: Float>(range: Rangef<S>, scale: S, pivot: S, offset: S) -> (S, S) {
+    let transform = |value: S, scale: S| pivot + (value - pivot) * scale;
+    let bounds = |scale: S, offset: S| {
+        let (a, b) = (transform(range.min, scale), transform(range.max, scale));
+        (a.min(b) + offset, a.max(b) + offset)
+    };
+    let (min, max) = bounds(scale, offset);
+    if min >= S::ZERO && max <= S::ONE {
+        return (scale, offset);
+    }
+
+    let (mut lowest, mut highest) = (S::NEG_INFINITY, S::INFINITY);
+    for distance in [range.min - pivot, range.max - pivot] {
+        if distance == S::ZERO {
+            continue;
+        }
+
+        let (a, b) = ((S::ZERO - pivot) / distance, (S::ONE - pivot) / distance);
+        lowest = lowest.max(a.min(b));
+        highest = highest.min(a.max(b));
+    }
+    let scale = if lowest <= highest {
+        scale.clamp(lowest, highest)
+    } else {
+        scale
+    };
+
+    let (min, max) = bounds(scale, S::ZERO);
+    let offset = if max - min <= S::ONE {
+        offset.clamp(S::ZERO - min, S::ONE - max)
+    } else {
+        offset
+    };
+
+    (scale, offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::math::{pos2, vec2, Rangef, Vec2};
+    use crate::{Curve, Point};
+
+    fn keys() -> Curve {
+        Curve::from_points([
+            Point::from_pos(pos2(0.0, 0.0)),
+            Point::from_pos(pos2(0.4, 0.2)),
+            Point::from_pos(pos2(0.6, 0.8)),
+            Point::from_pos(pos2(1.0, 1.0)),
+        ])
+    }
+
+    fn positions(curve: &Curve) -> Vec<(f32, f32)> {
+        curve
+            .points()
+            .iter()
+            .map(|point| (point.pos().x, point.pos().y))
+            .collect()
+    }
+
+    fn assert_positions(curve: &Curve, expected: &[(f32, f32)]) {
+        let positions = positions(curve);
+        assert_eq!(positions.len(), expected.len());
+        for (a, b) in positions.iter().zip(expected) {
+            assert!(
+                (a.0 - b.0).abs() < 0.0001 && (a.1 - b.1).abs() < 0.0001,
+                "{positions:?} != {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn scales_and_offsets_points() {
+        let mut curve = keys();
+        let indices = curve.transform_points(&[1, 2], vec2(0.5, 0.5), pos2(0.5, 0.5), Vec2::ZERO);
+        assert_eq!(indices, vec![1, 2]);
+        assert_positions(
+            &curve,
+            &[(0.0, 0.0), (0.45, 0.35), (0.55, 0.65), (1.0, 1.0)],
+        );
+
+        let indices = curve.retime_points(&[1], 0.2);
+        assert_eq!(indices, vec![2]);
+        assert_positions(
+            &curve,
+            &[(0.0, 0.0), (0.55, 0.65), (0.65, 0.35), (1.0, 1.0)],
+        );
+    }
+
+    #[test]
+    fn mirrors_tangents() {
+        let mut curve = Curve::from_points([
+            Point::new(
+                pos2(0.0, 0.0),
+                0.0,
+                0.5,
+                crate::TangentMode::Linear,
+                crate::TangentMode::Free,
+            ),
+            Point::from_pos(pos2(1.0, 1.0)),
+        ]);
+        curve.reverse();
+
+        let last = curve.points()[1];
+        assert_eq!(last.pos(), pos2(1.0, 0.0));
+        assert_eq!(last.left_tan(), -0.5);
+        assert!(last.right_mode() == crate::TangentMode::Linear);
+
+        curve.flip_vertical();
+        assert_eq!(curve.points()[1].pos(), pos2(1.0, 1.0));
+        assert_eq!(curve.points()[1].left_tan(), 0.5);
+    }
+
+    #[test]
+    fn keeps_moved_points_apart() {
+        // An offset past the edge stops at it, keeping the gap between the moved points
+        let mut curve = keys();
+        curve.transform_points(&[1, 2], Vec2::splat(1.0), pos2(0.0, 0.0), vec2(0.0, 0.5));
+        assert_positions(&curve, &[(0.0, 0.0), (0.4, 0.4), (0.6, 1.0), (1.0, 1.0)]);
+
+        let mut curve = keys();
+        curve.retime_points(&[1, 2], -2.0);
+        assert_positions(&curve, &[(0.0, 0.0), (0.0, 0.2), (0.2, 0.8), (1.0, 1.0)]);
+
+        // A scale past the edge is reduced until the points fit
+        let mut curve = keys();
+        curve.transform_points(&[1, 2], vec2(1.0, 10.0), pos2(0.5, 0.5), Vec2::ZERO);
+        assert_positions(&curve, &[(0.0, 0.0), (0.4, 0.0), (0.6, 1.0), (1.0, 1.0)]);
+
+        let mut curve = keys();
+        curve.scale(vec2(3.0, 1.0), pos2(0.5, 0.5));
+        assert_positions(&curve, &[(0.0, 0.0), (0.4, 0.2), (0.6, 0.8), (1.0, 1.0)]);
+    }
+
+    #[test]
+    fn normalizes_values() {
+        let mut curve = Curve::from_points([
+            Point::from_pos(pos2(0.0, 0.25)),
+            Point::from_pos(pos2(1.0, 0.5)),
+        ]);
+        curve.normalize(Rangef::new(0.0, 1.0));
+        assert_positions(&curve, &[(0.0, 0.0), (1.0, 1.0)]);
+
+        let indices = curve.normalize_points(&[0], Rangef::new(0.2, 0.6));
+        assert_eq!(indices, vec![0]);
+        assert_positions(&curve, &[(0.0, 0.4), (1.0, 1.0)]);
+    }
+
+    #[test]
+    fn ignores_degenerate_transforms() {
+        let mut empty = Curve::default();
+        empty.scale(vec2(2.0, 2.0), pos2(0.5, 0.5));
+        empty.normalize(Rangef::new(0.0, 1.0));
+        assert!(empty.points().is_empty());
+
+        let mut curve = keys();
+        let original = positions(&curve);
+        assert_eq!(
+            curve.transform_points(&[1], vec2(0.0, 1.0), pos2(0.5, 0.5), Vec2::ZERO),
+            vec![1]
+        );
+        assert_eq!(
+            curve.transform_points(&[1], vec2(f32::NAN, 1.0), pos2(0.5, 0.5), Vec2::ZERO),
+            vec![1]
+        );
+        assert_eq!(
+            curve.transform_points(&[7], Vec2::splat(1.0), pos2(0.5, 0.5), vec2(0.1, 0.1)),
+            vec![7]
+        );
+        assert_eq!(positions(&curve), original);
+    }
+}
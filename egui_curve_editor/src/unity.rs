@@ -0,0 +1,492 @@
+// Unity `AnimationCurve`, as serialized in the YAML of assets, prefabs and animation clips.
+//
+// Unity keys live in arbitrary time and value ranges, they are mapped to `0..=1` on import. Weighted
+// tangents are folded into the slope and constant tangents become flat, since `Curve` only knows
+// unweighted tangents. Neither survives a round trip: keys are written back unweighted, with the
+// slope that keeps the height of their control points.
+
+use crate::math::{pos2, Rangef};
+use crate::prelude::*;
+use crate::{Curve, ParseError, Point, TangentMode};
+
+/// What a Unity curve does before its first and after its last key.
+#[derive(PartialEq, Clone, Copy, Debug, Default)]
+pub enum UnityWrapMode {
+    PingPong,
+    Loop,
+    #[default]
+    Clamp,
+}
+
+impl UnityWrapMode {
+    fn from_int(value: i32) -> Option<Self> {
+        match value {
+            0 => Some(UnityWrapMode::PingPong),
+            1 => Some(UnityWrapMode::Loop),
+            2 => Some(UnityWrapMode::Clamp),
+            _ => None,
+        }
+    }
+
+    fn to_int(self) -> i32 {
+        match self {
+            UnityWrapMode::PingPong => 0,
+            UnityWrapMode::Loop => 1,
+            UnityWrapMode::Clamp => 2,
+        }
+    }
+}
+
+/// A [`Curve`] together with the ranges and wrap modes of the Unity curve it maps to.
+#[derive(Clone)]
+pub struct UnityCurve {
+    pub curve: Curve,
+    pub time_range: Rangef,
+    pub value_range: Rangef,
+    pub pre_infinity: UnityWrapMode,
+    pub post_infinity: UnityWrapMode,
+}
+
+impl Default for UnityCurve {
+    fn default() -> Self {
+        Self {
+            curve: Curve::default(),
+            time_range: Rangef::new(0.0, 1.0),
+            value_range: Rangef::new(0.0, 1.0),
+            pre_infinity: UnityWrapMode::default(),
+            post_infinity: UnityWrapMode::default(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Keyframe {
+    time: f32,
+    value: f32,
+    in_slope: f32,
+    out_slope: f32,
+    tangent_mode: i32,
+    weighted_mode: i32,
+    in_weight: f32,
+    out_weight: f32,
+}
+
+// Bit layout of `tangentMode`
+const BROKEN: i32 = 1;
+const LEFT_SHIFT: i32 = 1;
+const RIGHT_SHIFT: i32 = 5;
+const MODE_MASK: i32 = 0xF;
+
+// Values of `TangentUtility.TangentMode`
+const UNITY_FREE: i32 = 0;
+const UNITY_LINEAR: i32 = 2;
+const UNITY_CONSTANT: i32 = 3;
+
+// Values of `weightedMode`
+const WEIGHTED_IN: i32 = 1;
+const WEIGHTED_OUT: i32 = 2;
+
+const DEFAULT_WEIGHT: f32 = 1.0 / 3.0;
+
+#[allow(unused)]
+impl UnityCurve {
+    pub fn new(curve: Curve) -> Self {
+        Self {
+            curve,
+            ..Default::default()
+        }
+    }
+
+    /// Reads the first `m_Curve` key list found in the YAML, along with its wrap modes.
+    ///
+    /// The time and value ranges are taken from the keys. Weights are folded into the slopes and not
+    /// kept.
+    pub fn from_yaml(text: &str) -> Result<Self, ParseError> {
+        let lines: Vec<_> = text.lines().collect();
+
+        let (start, indent) = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| {
+                let trimmed = line.trim();
+                trimmed == "m_Curve:" || trimmed == "m_Curve: []"
+            })
+            .map(|(i, line)| (i, indentation(line)))
+            .find(|&(i, indent)| {
+                // An `m_Curve` holding a map is not a key list, the keys come at the same indentation
+                lines[i + 1..]
+                    .iter()
+                    .find(|line| !line.trim().is_empty())
+                    .is_none_or(|next| indentation(next) <= indent)
+            })
+            .ok_or_else(|| ParseError::new("no AnimationCurve keys found"))?;
+
+        let mut result = Self::default();
+        let mut keys: Vec<Keyframe> = vec![];
+
+        for (i, line) in lines.iter().enumerate().skip(start + 1) {
+            let line_number = i + 1;
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+
+            let line_indent = indentation(line);
+            if line_indent < indent {
+                break;
+            }
+
+            let (entry, is_new_key) = match trimmed.strip_prefix("- ") {
+                Some(entry) if line_indent == indent => (entry, true),
+                _ => (trimmed, false),
+            };
+
+            let Some((key, value)) = entry.split_once(':') else {
+                return Err(ParseError::at_line(line_number, "expected `key: value`"));
+            };
+            let value = value.trim();
+
+            if is_new_key {
+                keys.push(Keyframe {
+                    in_weight: DEFAULT_WEIGHT,
+                    out_weight: DEFAULT_WEIGHT,
+                    ..Default::default()
+                });
+            }
+
+            if line_indent == indent && !is_new_key {
+                // Fields of the curve itself, after the keys
+                match key {
+                    "m_PreInfinity" => result.pre_infinity = parse_wrap_mode(value, line_number)?,
+                    "m_PostInfinity" => result.post_infinity = parse_wrap_mode(value, line_number)?,
+                    _ => {}
+                }
+
+                continue;
+            }
+
+            let Some(keyframe) = keys.last_mut() else {
+                return Err(ParseError::at_line(line_number, "field outside of a key"));
+            };
+
+            match key {
+                "time" => keyframe.time = parse_float(value, line_number)?,
+                "value" => keyframe.value = parse_float(value, line_number)?,
+                "inSlope" => keyframe.in_slope = parse_float(value, line_number)?,
+                "outSlope" => keyframe.out_slope = parse_float(value, line_number)?,
+                "tangentMode" => keyframe.tangent_mode = parse_int(value, line_number)?,
+                "weightedMode" => keyframe.weighted_mode = parse_int(value, line_number)?,
+                "inWeight" => keyframe.in_weight = parse_float(value, line_number)?,
+                "outWeight" => keyframe.out_weight = parse_float(value, line_number)?,
+                _ => {}
+            }
+        }
+
+        if keys.is_empty() {
+            return Ok(result);
+        }
+
+        result.time_range = Rangef::new(
+            keys.iter()
+                .map(|key| key.time)
+                .fold(f32::INFINITY, f32::min),
+            keys.iter()
+                .map(|key| key.time)
+                .fold(f32::NEG_INFINITY, f32::max),
+        );
+        result.value_range = Rangef::new(
+            keys.iter()
+                .map(|key| key.value)
+                .fold(f32::INFINITY, f32::min),
+            keys.iter()
+                .map(|key| key.value)
+                .fold(f32::NEG_INFINITY, f32::max),
+        );
+
+        // Flat ranges still need a unit to normalize with
+        if result.time_range.span() <= 0.0 {
+            result.time_range.max = result.time_range.min + 1.0;
+        }
+        if result.value_range.span() <= 0.0 {
+            result.value_range.max = result.value_range.min + 1.0;
+        }
+
+        let scale = result.time_range.span() / result.value_range.span();
+        let points = keys.iter().map(|key| {
+            let left_mode = (key.tangent_mode >> LEFT_SHIFT) & MODE_MASK;
+            let right_mode = (key.tangent_mode >> RIGHT_SHIFT) & MODE_MASK;
+
+            // A weight moves the control point, keep its height with the fixed third of `Curve`
+            let mut in_slope = key.in_slope;
+            if key.weighted_mode & WEIGHTED_IN != 0 {
+                in_slope *= key.in_weight / DEFAULT_WEIGHT;
+            }
+            let mut out_slope = key.out_slope;
+            if key.weighted_mode & WEIGHTED_OUT != 0 {
+                out_slope *= key.out_weight / DEFAULT_WEIGHT;
+            }
+
+            Point::new(
+                pos2(
+                    (key.time - result.time_range.min) / result.time_range.span(),
+                    (key.value - result.value_range.min) / result.value_range.span(),
+                ),
+                slope_from_unity(in_slope, left_mode) * scale,
+                slope_from_unity(out_slope, right_mode) * scale,
+                mode_from_unity(left_mode),
+                mode_from_unity(right_mode),
+            )
+        });
+
+        result.curve = Curve::from_points(points);
+
+        Ok(result)
+    }
+
+    /// Writes the fields of a serialized `AnimationCurve`, without indentation.
+    ///
+    /// Keys are unweighted, weights read by [`UnityCurve::from_yaml`] are already in the slopes.
+    pub fn to_yaml(&self) -> String {
+        let scale = self.value_range.span() / self.time_range.span();
+
+        let mut text = String::from("serializedVersion: 2\n");
+        if self.curve.points().is_empty() {
+            text += "m_Curve: []\n";
+        } else {
+            text += "m_Curve:\n";
+        }
+
+        for point in self.curve.points() {
+            let pos = point.pos();
+            let left_mode = mode_to_unity(point.left_mode());
+            let right_mode = mode_to_unity(point.right_mode());
+
+            let mut tangent_mode = (left_mode << LEFT_SHIFT) | (right_mode << RIGHT_SHIFT);
+            if point.left_tan() != point.right_tan() || left_mode != right_mode {
+                tangent_mode |= BROKEN;
+            }
+
+            text += "- serializedVersion: 3\n";
+            text += &format!(
+                "  time: {}\n",
+                self.time_range.min + pos.x * self.time_range.span()
+            );
+            text += &format!(
+                "  value: {}\n",
+                self.value_range.min + pos.y * self.value_range.span()
+            );
+            text += &format!("  inSlope: {}\n", point.left_tan() * scale);
+            text += &format!("  outSlope: {}\n", point.right_tan() * scale);
+            text += &format!("  tangentMode: {}\n", tangent_mode);
+            text += "  weightedMode: 0\n";
+            text += &format!("  inWeight: {}\n", DEFAULT_WEIGHT);
+            text += &format!("  outWeight: {}\n", DEFAULT_WEIGHT);
+        }
+
+        text += &format!("m_PreInfinity: {}\n", self.pre_infinity.to_int());
+        text += &format!("m_PostInfinity: {}\n", self.post_infinity.to_int());
+        text += "m_RotationOrder: 4\n";
+
+        text
+    }
+}
+
+#[allow(unused)]
+impl Curve {
+    pub fn from_unity_yaml(text: &str) -> Result<Self, ParseError> {
+        UnityCurve::from_yaml(text).map(|unity| unity.curve)
+    }
+
+    pub fn to_unity_yaml(&self) -> String {
+        UnityCurve::new(self.clone()).to_yaml()
+    }
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+fn parse_float(text: &str, line_number: usize) -> Result<f32, ParseError> {
+    text.parse()
+        .map_err(|_| ParseError::at_line(line_number, format!("invalid number `{text}`")))
+}
+
+fn parse_int(text: &str, line_number: usize) -> Result<i32, ParseError> {
+    text.parse()
+        .map_err(|_| ParseError::at_line(line_number, format!("invalid integer `{text}`")))
+}
+
+fn parse_wrap_mode(text: &str, line_number: usize) -> Result<UnityWrapMode, ParseError> {
+    UnityWrapMode::from_int(parse_int(text, line_number)?)
+        .ok_or_else(|| ParseError::at_line(line_number, format!("invalid wrap mode `{text}`")))
+}
+
+fn mode_from_unity(mode: i32) -> TangentMode {
+    match mode {
+        UNITY_LINEAR => TangentMode::Linear,
+        _ => TangentMode::Free,
+    }
+}
+
+fn mode_to_unity(mode: TangentMode) -> i32 {
+    match mode {
+        TangentMode::Free => UNITY_FREE,
+        TangentMode::Linear => UNITY_LINEAR,
+    }
+}
+
+fn slope_from_unity(slope: f32, mode: i32) -> f32 {
+    if mode == UNITY_CONSTANT || !slope.is_finite() {
+        return 0.0;
+    }
+
+    slope
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::math::Pos2;
+
+    const ASSET: &str = include_str!("../testdata/unity_asset.asset");
+    const CLIP: &str = include_str!("../testdata/unity_clip.anim");
+
+    fn assert_point(point: &Point, pos: Pos2, left_tan: f32, right_tan: f32) {
+        assert!(point.pos().distance(pos) < 0.0001, "{:?}", point.pos());
+        assert!((point.left_tan() - left_tan).abs() < 0.0001);
+        assert!((point.right_tan() - right_tan).abs() < 0.0001);
+    }
+
+    fn assert_same_points(a: &Curve, b: &Curve) {
+        assert_eq!(a.points().len(), b.points().len());
+        for (a, b) in a.points().iter().zip(b.points()) {
+            assert_point(a, b.pos(), b.left_tan(), b.right_tan());
+            assert!(a.left_mode() == b.left_mode() && a.right_mode() == b.right_mode());
+        }
+    }
+
+    #[test]
+    fn reads_assets() {
+        let unity = UnityCurve::from_yaml(ASSET).unwrap();
+
+        assert_eq!(unity.time_range, Rangef::new(0.0, 2.0));
+        assert_eq!(unity.value_range, Rangef::new(0.0, 4.0));
+        assert_eq!(unity.pre_infinity, UnityWrapMode::PingPong);
+        assert_eq!(unity.post_infinity, UnityWrapMode::Loop);
+
+        // Slopes are scaled by a time range of 2 over a value range of 4, the weight of 0.5 on the
+        // first key makes its slope 1.5 times steeper
+        let points = unity.curve.points();
+        assert_eq!(points.len(), 3);
+        assert_point(&points[0], pos2(0.0, 0.0), 0.0, 1.5);
+        assert_point(&points[1], pos2(0.5, 1.0), 0.0, 0.0);
+        assert_point(&points[2], pos2(1.0, 0.5), -1.0, 0.0);
+        assert!(points[0].right_mode() == TangentMode::Free);
+        assert!(points[2].left_mode() == TangentMode::Linear);
+        assert!(points[2].right_mode() == TangentMode::Linear);
+    }
+
+    #[test]
+    fn round_trips_assets() {
+        let unity = UnityCurve::from_yaml(ASSET).unwrap();
+        let text = unity.to_yaml();
+
+        // Ranges and wrap modes come back, the weight is folded into the slope
+        assert!(text.contains("m_PreInfinity: 0\n"));
+        assert!(text.contains("m_PostInfinity: 1\n"));
+        assert!(text.contains("  outSlope: 3\n"));
+        assert!(!text.contains("weightedMode: 2"));
+        assert!(text.contains("  tangentMode: 69\n"));
+
+        let again = UnityCurve::from_yaml(&text).unwrap();
+        assert_eq!(again.time_range, unity.time_range);
+        assert_eq!(again.value_range, unity.value_range);
+        assert_eq!(again.pre_infinity, unity.pre_infinity);
+        assert_eq!(again.post_infinity, unity.post_infinity);
+        assert_same_points(&again.curve, &unity.curve);
+
+        let curve = Curve::from_unity_yaml(ASSET).unwrap();
+        assert_same_points(
+            &Curve::from_unity_yaml(&curve.to_unity_yaml()).unwrap(),
+            &curve,
+        );
+    }
+
+    #[test]
+    fn reads_animation_clips() {
+        let unity = UnityCurve::from_yaml(CLIP).unwrap();
+
+        assert_eq!(unity.time_range, Rangef::new(0.0, 0.5));
+        assert_eq!(unity.post_infinity, UnityWrapMode::Clamp);
+
+        // Only the first curve of the clip is read
+        let points = unity.curve.points();
+        assert_eq!(points.len(), 2);
+        assert_point(&points[0], pos2(0.0, 1.0), 0.0, 0.0);
+        assert_point(&points[1], pos2(1.0, 0.0), -2.0, -2.0);
+
+        let again = UnityCurve::from_yaml(&unity.to_yaml()).unwrap();
+        assert_eq!(again.time_range, unity.time_range);
+        assert_same_points(&again.curve, &unity.curve);
+    }
+
+    #[test]
+    fn skips_curves_holding_a_map() {
+        let text = "\
+AnimationClip:
+  m_Curve:
+    serializedVersion: 2
+    m_Name: Outer
+  m_FloatCurves:
+  - curve:
+      serializedVersion: 2
+      m_Curve:
+      - serializedVersion: 3
+        time: 0
+        value: 0
+        inSlope: 1
+        outSlope: 1
+        tangentMode: 0
+      - serializedVersion: 3
+        time: 1
+        value: 1
+        inSlope: 1
+        outSlope: 1
+        tangentMode: 0
+      m_PreInfinity: 2
+      m_PostInfinity: 2
+";
+        let curve = Curve::from_unity_yaml(text).unwrap();
+
+        assert_eq!(curve.points().len(), 2);
+        assert_point(&curve.points()[1], pos2(1.0, 1.0), 1.0, 1.0);
+    }
+
+    #[test]
+    fn reads_empty_curves() {
+        let unity = UnityCurve::from_yaml(
+            "curve:\n  serializedVersion: 2\n  m_Curve: []\n  m_PreInfinity: 1\n",
+        )
+        .unwrap();
+        assert!(unity.curve.points().is_empty());
+
+        let text = Curve::default().to_unity_yaml();
+        assert!(text.contains("m_Curve: []\n"));
+        assert!(Curve::from_unity_yaml(&text).unwrap().points().is_empty());
+    }
+
+    #[test]
+    fn rejects_invalid_yaml() {
+        assert!(Curve::from_unity_yaml("m_Name: Nothing\n").is_err());
+
+        let error = Curve::from_unity_yaml("m_Curve:\n- time: soon\n")
+            .err()
+            .unwrap();
+        assert_eq!(error.line, Some(2));
+
+        let error = Curve::from_unity_yaml("m_Curve:\n- time: 0\nm_PreInfinity: 7\n")
+            .err()
+            .unwrap();
+        assert_eq!(error.line, Some(3));
+    }
+}
@@ -1,5 +1,8 @@
 use eframe::egui;
-use egui_curve_editor::{Curve, CurveEditor};
+use egui_curve_editor::{
+    Curve, CurveEditor, CurvePlayer, DopeSheet, Envelope, EnvelopeEditor, Gradient, GradientEditor,
+    Key, KeyedCurve, KeyedCurveEditor, PlayMode, TimeDisplay, Timeline, Track,
+};
 
 // TODO: Remove main file
 fn main() {
@@ -8,27 +11,88 @@ fn main() {
         ..Default::default()
     };
 
-    let mut curve = Curve::linear();
+    let mut player = CurvePlayer::new(Curve::linear(), 10.0).with_mode(PlayMode::Loop);
+    let mut color = KeyedCurve::from_keys([
+        Key::new(0.0, egui::Rgba::from_rgb(1.0, 0.3, 0.1)),
+        Key::new(1.0, egui::Rgba::from_rgb(0.1, 0.4, 1.0)),
+    ]);
+    let mut gradient = Gradient::new(egui::Color32::YELLOW, egui::Color32::from_rgb(200, 0, 80));
+    let mut draw_mode = false;
+    let mut draw_smoothness = 0.01;
+    let mut frames = false;
+    let timeline = Timeline::new(10.0, 24.0).with_display(TimeDisplay::Timecode);
+    let mut tracks = vec![
+        Track::new("Position", Curve::linear()),
+        Track::new("Scale", Curve::linear()),
+        Track::new("Opacity", Curve::linear()),
+    ];
+    let mut envelope = Envelope::default();
 
-    let start = std::time::Instant::now();
-    let mut now = start;
+    let mut now = std::time::Instant::now();
 
     eframe::run_simple_native("Curve Editor Demo", options, move |ctx, _frame| {
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.heading("Curve Editor");
-            let elapsed = start.elapsed().as_secs_f32() % 10.0;
+            player.advance(ui.input(|i| i.stable_dt));
             ui.label(format!(
                 "Sample at {:.3}: {:.3}",
-                elapsed / 10.0,
-                curve.sample(elapsed / 10.0)
+                player.progress(),
+                player.value()
             ));
             ui.label(format!("frame time: {}ms", now.elapsed().as_millis()));
             now = std::time::Instant::now();
 
-            ui.add(CurveEditor::new(&mut curve).with_max_size(egui::vec2(400.0, 100.0)));
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut draw_mode, "Pencil");
+                ui.add_enabled(
+                    draw_mode,
+                    egui::Slider::new(&mut draw_smoothness, 0.001..=0.1)
+                        .logarithmic(true)
+                        .text("smoothness"),
+                );
+                ui.checkbox(&mut frames, "Frames");
+            });
+
+            let progress = player.progress();
+            let mut editor = CurveEditor::new(player.curve_mut())
+                .with_playhead(progress)
+                .with_max_size(egui::vec2(400.0, 100.0))
+                .with_overshoot_highlight(true)
+                .with_paste_fit(0.01)
+                .with_draw_mode(draw_mode)
+                .with_draw_smoothness(draw_smoothness);
+            if frames {
+                editor = editor.with_timeline(timeline);
+            }
+            ui.add(editor);
 
             ui.label("sample text to test height of widget");
 
+            ui.horizontal(|ui| {
+                ui.label("Color");
+                let (rect, _) =
+                    ui.allocate_exact_size(egui::vec2(16.0, 16.0), egui::Sense::hover());
+                ui.painter().rect_filled(rect, 2, color.sample(progress));
+            });
+            ui.add(KeyedCurveEditor::new(&mut color).with_max_size(egui::vec2(400.0, 100.0)));
+
+            ui.label("Gradient");
+            ui.add(GradientEditor::new(&mut gradient).with_width(400.0));
+
+            ui.label("Dope sheet");
+            ui.add(
+                DopeSheet::new(&mut tracks)
+                    .with_width(400.0)
+                    .with_timeline(timeline),
+            );
+
+            ui.label("Envelope");
+            ui.add(
+                EnvelopeEditor::new(&mut envelope)
+                    .with_width(400.0)
+                    .with_height(100.0),
+            );
+
             ctx.request_repaint();
         });
     })